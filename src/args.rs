@@ -0,0 +1,214 @@
+use rufetch::modules::MODULE_NAMES;
+use std::{env, path::PathBuf, time::Duration};
+
+/// Seconds between two fetches of `--watch` without `--interval`.
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The longest `--interval` in seconds, a week.
+const MAX_INTERVAL_SECS: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+/// How the fetch is printed, chosen with `--format`.
+#[derive(Default, PartialEq)]
+pub enum Format {
+    /// Labeled lines with colors, the default.
+    #[default]
+    Text,
+    /// The values on a single line separated by ` | `.
+    Oneline,
+    /// A single JSON object.
+    Json,
+    /// Uncolored `key=value` lines with the keys of the JSON output.
+    Kv,
+}
+
+/// Options given on the command line.
+#[derive(Default)]
+pub struct Args {
+    /// Config file to load instead of the default one.
+    pub config: Option<PathBuf>,
+
+    /// Format to print the fetch in, `--json` and `--oneline` are short for two of them.
+    pub format: Format,
+
+    /// Print plain text without ANSI colors.
+    pub no_color: bool,
+
+    /// Write the default config file and exit.
+    pub gen_config: bool,
+
+    /// Let `--gen-config` overwrite an existing file.
+    pub force: bool,
+
+    /// File to write the output to instead of stdout.
+    pub output: Option<PathBuf>,
+
+    /// Keep the ANSI colors in the `--output` file.
+    pub ansi: bool,
+
+    /// Print the fetch again after every interval, `--interval` seconds or 2 by default.
+    pub watch: Option<Duration>,
+
+    /// Print the name and version of RuFetch and exit.
+    pub version: bool,
+
+    /// Show every module and detail on this run.
+    pub all: bool,
+
+    /// Print the available modules and whether they are enabled, then exit.
+    pub list_modules: bool,
+
+    /// Module names given to `--fields`, the only ones printed on this run.
+    pub fields: Option<Vec<String>>,
+}
+
+impl Args {
+    /// Parses the arguments the program was started with.
+    ///
+    /// # Errors
+    /// Returns a message describing the first unknown or incomplete argument.
+    pub fn parse() -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut args = env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--json" => parsed.format = Format::Json,
+                "--oneline" => parsed.format = Format::Oneline,
+                "--no-color" => parsed.no_color = true,
+                "--gen-config" => parsed.gen_config = true,
+                "--force" => parsed.force = true,
+                "--ansi" => parsed.ansi = true,
+                "--list-modules" => parsed.list_modules = true,
+                "--all" | "-a" => parsed.all = true,
+                "--version" | "-V" => parsed.version = true,
+                "--watch" | "-w" => {
+                    parsed.watch = parsed.watch.or(Some(DEFAULT_WATCH_INTERVAL));
+                }
+                "--interval" => {
+                    let seconds = args.next().unwrap_or_default();
+                    parsed.watch = Some(Args::parse_interval(&seconds)?);
+                }
+                "--config" | "-c" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| format!("`{}` needs a path", arg))?;
+                    parsed.config = Some(PathBuf::from(path));
+                }
+                "--output" | "-o" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| format!("`{}` needs a path", arg))?;
+                    parsed.output = Some(PathBuf::from(path));
+                }
+                "--format" => {
+                    let format = args.next().unwrap_or_default();
+                    parsed.format = Args::parse_format(&format)?;
+                }
+                "--fields" => {
+                    let fields = args.next().unwrap_or_default();
+                    parsed.fields = Some(Args::parse_fields(&fields)?);
+                }
+                _ => {
+                    if let Some(path) = arg.strip_prefix("--config=") {
+                        parsed.config = Some(PathBuf::from(path));
+                    } else if let Some(path) = arg.strip_prefix("--output=") {
+                        parsed.output = Some(PathBuf::from(path));
+                    } else if let Some(seconds) = arg.strip_prefix("--interval=") {
+                        parsed.watch = Some(Args::parse_interval(seconds)?);
+                    } else if let Some(format) = arg.strip_prefix("--format=") {
+                        parsed.format = Args::parse_format(format)?;
+                    } else if let Some(fields) = arg.strip_prefix("--fields=") {
+                        parsed.fields = Some(Args::parse_fields(fields)?);
+                    } else {
+                        return Err(format!("Unknown argument `{}`", arg));
+                    }
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Parses the `--interval` seconds, which may have a fraction.
+    ///
+    /// # Errors
+    /// Returns a message if `seconds` isn't a positive number up to [MAX_INTERVAL_SECS].
+    fn parse_interval(seconds: &str) -> Result<Duration, String> {
+        match seconds.parse::<f64>() {
+            // Duration::from_secs_f64 panics on values too big for a Duration
+            Ok(seconds) if seconds > 0.0 && seconds <= MAX_INTERVAL_SECS => {
+                Ok(Duration::from_secs_f64(seconds))
+            }
+            _ => Err(format!(
+                "`--interval` needs a positive number of seconds, got `{}`",
+                seconds
+            )),
+        }
+    }
+
+    /// Parses the name given to `--format`.
+    ///
+    /// # Errors
+    /// Returns a message listing the formats if `name` isn't one of them.
+    fn parse_format(name: &str) -> Result<Format, String> {
+        match name {
+            "text" => Ok(Format::Text),
+            "oneline" => Ok(Format::Oneline),
+            "json" => Ok(Format::Json),
+            "kv" => Ok(Format::Kv),
+            _ => Err(format!(
+                "Unknown format `{}`, expected one of: text, oneline, json, kv",
+                name
+            )),
+        }
+    }
+
+    /// Splits the comma-separated `--fields` list into module names.
+    ///
+    /// # Errors
+    /// Returns a message listing the valid names if the list is empty or has an unknown name.
+    fn parse_fields(list: &str) -> Result<Vec<String>, String> {
+        let fields: Vec<String> = list
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(String::from)
+            .collect();
+
+        let valid = MODULE_NAMES.join(", ");
+        if fields.is_empty() {
+            return Err(format!(
+                "`--fields` needs a comma-separated list of fields, any of: {}",
+                valid
+            ));
+        }
+        if let Some(field) = fields
+            .iter()
+            .find(|field| !MODULE_NAMES.contains(&field.as_str()))
+        {
+            return Err(format!(
+                "Unknown field `{}`, expected any of: {}",
+                field, valid
+            ));
+        }
+
+        Ok(fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_are_positive_and_bounded() {
+        assert_eq!(Args::parse_interval("0.5"), Ok(Duration::from_millis(500)));
+        assert_eq!(
+            Args::parse_interval("604800"),
+            Ok(Duration::from_secs(604_800))
+        );
+        for invalid in ["0", "-1", "abc", "NaN", "inf", "1e300", "604801"] {
+            assert!(Args::parse_interval(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
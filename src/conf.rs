@@ -1,353 +1,862 @@
-use crate::types::{Config, MemType, Time};
-use ansi_term::{self, Color::*};
-pub use serde::Deserialize;
+use crate::json::Value;
+use crate::logo::Logo;
+use crate::modules::*;
+use crate::types::{
+    default_disk_crit_percent, default_disk_warn_percent, default_label_color, default_temp_crit,
+    default_temp_warn, parse_color, Config, ConfigError,
+};
+use crate::yaml;
+use ansi_term::{
+    Color::{self, Blue, Green, Red, Yellow},
+    Style,
+};
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, io, str};
+use std::{io::Write, thread};
+use sysinfo::{CpuRefreshKind, ProcessRefreshKind, RefreshKind, System, SystemExt};
+
+/// Minimum time between the two CPU refreshes sysinfo needs to compute usage.
+const CPU_SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Warning and critical usage percentages of memory and swap.
+pub const MEMORY_THRESHOLDS: (u8, u8) = (70, 90);
+
+/// Returns green below the warning percentage, yellow up to the critical one and red above.
+pub fn usage_color(percent: f64, (warn, crit): (u8, u8)) -> Color {
+    if percent >= f64::from(crit) {
+        Red
+    } else if percent >= f64::from(warn) {
+        Yellow
+    } else {
+        Green
+    }
+}
+
+/// Default configs.
+/// These are completely ignored in case a config file is found, keys missing from
+/// the config file fall back to the per-field defaults in types.rs instead, so the
+/// two have to be kept in sync.
+const DEFAULT_CONFIG: &str = r##"# RuFetch config
+# Any option left out falls back to its default.
+
+# Fields to show
+# The user@host title with the separator below it, and the Host line.
+# Both use show_hostname when left out, which older configs use for the two of them.
+show_hostname = true
+# show_title = true
+# show_host = true
+show_model = true
+# Hypervisor and container runtime, skipped on bare metal (Linux only)
+show_virt = false
+show_os = true
+show_de = true
+show_wm = true
+# X11 or Wayland
+show_display_server = true
+show_packages = true
+show_shell = true
+show_terminal = true
+# Locale from LC_ALL or LANG, e.g. en_US.UTF-8
+show_locale = false
+show_uptime = true
+# Local date and time of the last boot
+show_boot_time = false
+# Current local date and time
+show_datetime = false
+# Name of the local timezone, e.g. CET
+show_timezone = false
+# Format of show_datetime, show_boot_time and uptime_type "Both": %Y year, %m month,
+# %d day, %H hour, %M minute, %S second, %Z timezone
+datetime_format = "%Y-%m-%d %H:%M"
+# 1, 5 and 15 minute load averages, not available on Windows
+show_load_avg = false
+# Number of running processes
+show_processes = false
+show_kernel_version = true
+# CPU architecture after the kernel version, e.g. "(x86_64)"
+show_arch = false
+# Init system, e.g. systemd (Linux only)
+show_init = false
+show_disks = true
+# Mount point and filesystem of each disk, e.g. "Disk: /dev/sda2 (/) ext4"
+show_disk_mount = false
+show_disk_fs = false
+# Only show disks mounted below these paths, all of them when empty
+disk_include = []
+# Hide disks mounted below these paths
+disk_exclude = ["/snap", "/boot/efi"]
+hide_removable = false
+# Exactly the disks mounted at these paths in this order, ignoring the filters above
+# disks = ["/", "/home"]
+# Sum of the shown disks
+show_disk_total = false
+show_cpu = true
+# Total CPU cores
+show_cores = true
+# Physical cores and threads, e.g. (8C/16T), when they differ
+show_threads = false
+# Current CPU load, needs a short extra sample
+show_cpu_usage = false
+show_cpu_freq = false
+# Microarchitecture after the CPU's name where known, e.g. (Zen 3)
+show_cpu_codename = false
+# Usage of each core instead of the core count
+show_per_core_usage = false
+# CPU package temperature as a line of its own, may not work on all platforms
+show_cpu_temp = false
+# Total L2 and L3 cache as a line of its own (Linux and Windows)
+show_cpu_cache = false
+show_gpu = true
+show_resolution = true
+show_memory = true
+# Usage percentage after memory and swap
+show_memory_percent = false
+# Available and free memory and free swap after the used and total sizes
+memory_detailed = false
+# Usage bars with the percentage after memory, swap and disks
+usage_bars = false
+# Bars of memory and swap on their own, use usage_bars when left out
+# memory_bar = true
+# swap_bar = false
+bar_width = 20
+
+# Disk usage turns yellow from disk_warn_percent and red from disk_crit_percent
+disk_warn_percent = 70
+disk_crit_percent = 90
+show_swap = true
+# Show the swap line even when there is no swap
+force_swap = false
+# Only the used percentage of the swap, e.g. "Swap: 3%"
+swap_percent_only = false
+show_local_ip = false
+# Data received and sent by each interface since boot
+show_network = false
+show_battery = true
+# May not work on all platforms
+show_temperature = false
+show_colors = true
+# Every module and detail above, e.g. for bug reports, same as --all
+show_all = false
+
+# Which modules to print and in what order, replacing the show_* options above, e.g.
+# modules = ["title", "os", "kernel", "cpu", "memory", "colors"]
+# Available: title, host, model, virt, os, de, wm, display_server, packages, shell, terminal,
+# locale, uptime, boot_time, datetime, timezone, load_avg, processes, kernel, init, disks,
+# cpu, cpu_temp, cpu_cache, gpu, resolution, memory, swap, local_ip, network, battery,
+# temperature, colors
+# ASCII art of the distro beside the info
+show_logo = false
+
+# A preset look: default, mono (no colors) or minimal (no separator, compact colors).
+# The options a theme sets are left out below, setting them here wins over the theme.
+# theme = "default"
+
+# The line below user@host
+separator_char = "-"
+# separator_length = 30
+# As long as the user@host title instead of separator_length
+separator_match_title = false
+
+# Nerd Font icons before the labels, see [icons] below
+show_icons = false
+
+# Pad labels to the longest one so the values line up
+align_labels = true
+# Lines longer than this are cut with "…", the terminal's width when left out
+# max_width = 80
+
+# black, red, green, yellow, blue, purple, cyan, white or a hex value like "#ff8800"
+label_color = "blue"
+# Same colors as label_color, values are uncolored when left out
+# value_color = "white"
+
+# Set to false for plain text without ANSI colors, same as NO_COLOR or --no-color
+# colored_output = true
+
+# Size of the color blocks
+colors_height = 2
+colors_width = 2
+# Second row of blocks, black, white, purple and cyan
+show_bright_colors = true
+# All colors in one narrow row, ignoring colors_height and colors_width
+# colors_compact = false
+# Exactly these colors as a single row of blocks, same names as label_color
+# color_palette = ["red", "yellow", "green", "cyan", "blue", "purple"]
+
+# Day, Hour, Minute, Second, Auto (e.g. "2d 4h 13m"), Human (e.g. "2 days, 4 hours, 13 mins")
+# or Both (e.g. "2d 4h 13m (since 2024-01-28 08:00)", formatted like datetime_format)
+uptime_type = "Hour"
+
+# KB, MB, GB, TB (powers of 1000), KiB, MiB, GiB (powers of 1024) or Auto
+# GiB on Linux, matching free -h, and GB elsewhere when left out
+# memory_type = "GiB"
+# Same units as memory_type, use memory_type when left out
+# swap_type = "GiB"
+# disk_type = "GiB"
+# Decimal places of the sizes and the uptime, e.g. 0 for "16 GB"
+precision = 2
+
+# C, F or K
+temperature_unit = "C"
+# Temperatures turn yellow from temp_warn and red from temp_crit, both in °C
+temp_warn = 70
+temp_crit = 85
+# Only show sensors whose label contains one of these, e.g. ["Core", "Package"], all when empty
+temp_filter = []
+
+# ASCII art file used by show_logo instead of the built-in logo, ANSI colors are kept
+# logo_path = "/path/to/logo.txt"
+
+# Labels replacing the built-in ones, by module name, "disk_total" names the disk sum
+# and "container" the container line of virt
+# [labels]
+# os = "Distro"
+# memory = "RAM"
+
+# Icons used by show_icons instead of the built-in ones, by module name
+# [icons]
+# os = "\uf17c"
+# memory = "\uf538"
+"##;
+
+/// A deserializer that only records the field names of the struct deserialized from it.
+struct FieldNames<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("only structs have field names"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(de::Error::custom("only the field names are recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
 
-use std::iter::repeat;
-use std::{fs::File, path::Path, str};
-use std::{io::Read, process::Command};
-use sysinfo::{ComponentExt, CpuExt, DiskExt, System, SystemExt};
+impl Default for Config {
+    fn default() -> Self {
+        toml::from_str(DEFAULT_CONFIG).expect("the built-in default config is valid")
+    }
+}
 
 impl Config {
     /// Fetches config and returns a new [Config] instance.
     ///
-    /// # Panic
-    /// This code should not panic under normal circumstances.
-
-    pub fn new() -> Config {
-        // Default configs.
-        // These are completely ignored in case a config file is found.
-        let default_config = r#"
-        show_os = true
-        show_hostname = true
-        show_uptime = true
-        show_kernel_version = true
-        show_memory = true
-        show_swap = true
-        show_de = true
-        show_colors = true
-        show_cpu = true
-        show_cores = true
-        show_disks = true
-        show_temperature = false
-        colors_height = 3
-        colors_width = 3
-        uptime_type = "Minute"
-        memory_type = "GB"
-    "#;
-
-        let config_path = std::format!(
-            "{}/ru_fetch/config.toml",
-            dirs::config_dir().unwrap().as_path().to_str().unwrap()
-        );
-
-        let config: Config = if Path::new(&config_path).exists() {
-            let f = File::open(&config_path); // no unwrap() since other errors can occur too.
-            match f {
-                Ok(mut file) => {
-                    let mut contents = String::new();
-                    match file.read_to_string(&mut contents) {
-                        Ok(_) => {}
-                        Err(_) => toml::from_str(default_config).unwrap(),
-                    }
-
-                    match toml::from_str(&contents) {
-                        Ok(config) => config,
-                        Err(error) => {
-                            println!(
-                                "{}",
-                                Red.bold()
-                                    .paint("Error in config, falling back to default config.")
-                            );
-                            println!(
-                                "{} {}",
-                                Red.bold().paint(error.to_string()),
-                                Blue.bold().paint("(line, column may differ from actual)")
-                            );
-                            toml::from_str(default_config).unwrap()
-                        }
-                    }
-                }
-                Err(_) => toml::from_str(default_config).unwrap(),
+    /// The config is read from `path` if given, then from `$RUFETCH_CONFIG`, and lastly from
+    /// `<config_dir>/ru_fetch/config.toml`. Only the last one may be missing, in which case
+    /// (or if there is no config directory) the defaults are used. `RUFETCH_SHOW_*`
+    /// environment variables override the `show_*` fields, see [Config::env_overrides].
+    ///
+    /// # Errors
+    /// Returns a [ConfigError] if the config file can't be read or parsed.
+    pub fn new(path: Option<PathBuf>) -> Result<Config, ConfigError> {
+        let (config_path, may_be_missing) =
+            match path.or_else(|| env::var_os("RUFETCH_CONFIG").map(PathBuf::from)) {
+                Some(path) => (path, false),
+                None => match Config::default_path() {
+                    Some(path) => (path, true),
+                    None => return Config::parse(Path::new(""), DEFAULT_CONFIG),
+                },
+            };
+
+        // Read straight away instead of checking for the file first, so it is only touched once
+        let contents = match fs::read_to_string(&config_path) {
+            Ok(contents) => contents,
+            Err(error) if may_be_missing && error.kind() == io::ErrorKind::NotFound => {
+                return Config::parse(Path::new(""), DEFAULT_CONFIG)
             }
-        } else {
-            toml::from_str(default_config).unwrap()
+            Err(error) => return Err(error.into()),
         };
 
-        config
+        Config::parse(&config_path, &contents)
     }
 
-    /// Prints the fetch results to the console.
-    ///
-    /// The result depends on the config file or the fallback defaults.
-
-    pub fn print(&self, sys: &System) {
-        if self.show_hostname {
-            let host_name = sys.host_name();
-            // Getting the user
-            let user = Config::get_user();
-
-            if let Some(host_name) = &host_name {
-                println!(
-                    "{}@{}",
-                    Blue.bold().paint(user),
-                    Blue.bold().paint(host_name)
-                );
+    /// Returns the defaults with the `RUFETCH_SHOW_*` overrides applied, as [Config::new]
+    /// loads them without a config file. Used in place of a config file that has errors.
+    pub fn fallback() -> Config {
+        Config::parse(Path::new(""), DEFAULT_CONFIG).unwrap_or_default()
+    }
+
+    /// Parses `contents` as JSON or YAML if `path` ends in `.json`, `.yaml` or `.yml`,
+    /// and as TOML otherwise.
+    fn parse(path: &Path, contents: &str) -> Result<Config, ConfigError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        let parsed = match extension.as_deref() {
+            Some("json") => Value::parse(contents),
+            Some("yaml" | "yml") => yaml::parse(contents),
+            // Deserializing straight from the text keeps the line numbers in errors
+            _ => {
+                return Config::with_overrides(toml::from_str(contents)?, toml::from_str(contents)?)
             }
+        };
 
-            println!("{}", "-".repeat(30));
-            Config::print_hostname(host_name);
-        }
+        // TOML has no null, so null values are left out like missing ones
+        let value = parsed
+            .map_err(ConfigError::Syntax)?
+            .into_toml()
+            .unwrap_or(toml::Value::Table(Default::default()));
+        Config::with_overrides(value.clone().try_into()?, value)
+    }
 
-        if self.show_os {
-            Config::print_os(&self, &sys);
+    /// Records the unknown keys of `value`, the table `config` was deserialized from, and
+    /// applies the `theme` below it and the `RUFETCH_SHOW_*` environment variables above it.
+    fn with_overrides(mut config: Config, mut value: toml::Value) -> Result<Config, ConfigError> {
+        let unknown_keys = Config::unknown_keys(&value);
+        let themed = Config::apply_theme(&mut value);
+        let mut invalid_env = Vec::new();
+        if Config::env_overrides(&mut value, &mut invalid_env) || themed {
+            config = value.try_into()?;
         }
+        config.unknown_keys = unknown_keys;
+        config.invalid_env = invalid_env;
+        Ok(config)
+    }
 
-        if self.show_de {
-            Config::print_desktop_environment();
-        }
+    /// Returns the options the theme called `name` sets, or `None` if there is no such theme.
+    fn theme(name: &str) -> Option<Vec<(&'static str, toml::Value)>> {
+        Some(match name {
+            "default" => Vec::new(),
+            "mono" => vec![("colored_output", toml::Value::Boolean(false))],
+            "minimal" => vec![
+                ("separator_length", toml::Value::Integer(0)),
+                ("colors_compact", toml::Value::Boolean(true)),
+            ],
+            _ => return None,
+        })
+    }
 
-        if self.show_uptime {
-            Config::print_uptime(&self, &sys);
-        }
+    /// Sets the options of the `theme` in `value` that it doesn't set itself.
+    /// Returns whether any was set, unknown themes are left to [Config::validate].
+    fn apply_theme(value: &mut toml::Value) -> bool {
+        let table = match value.as_table_mut() {
+            Some(table) => table,
+            None => return false,
+        };
+        let options = match table.get("theme").and_then(toml::Value::as_str) {
+            Some(name) => Config::theme(name).unwrap_or_default(),
+            None => return false,
+        };
 
-        if self.show_kernel_version {
-            Config::print_kernel_ver(&sys);
+        let mut found = false;
+        for (key, option) in options {
+            if !table.contains_key(key) {
+                table.insert(key.to_string(), option);
+                found = true;
+            }
         }
+        found
+    }
 
-        if self.show_disks {
-            Config::print_disks(&sys);
-        }
+    /// Sets every `show_*` field named by an environment variable in `value`, e.g.
+    /// `RUFETCH_SHOW_TEMPERATURE=1` sets `show_temperature = true`. Returns whether any was set.
+    /// Variables that aren't `1`, `0`, `true` or `false` are skipped and added to `invalid`.
+    fn env_overrides(value: &mut toml::Value, invalid: &mut Vec<String>) -> bool {
+        let table = match value.as_table_mut() {
+            Some(table) => table,
+            None => return false,
+        };
 
-        if self.show_cpu {
-            Config::print_cpu(&self, &sys);
+        let mut found = false;
+        for field in Config::field_names()
+            .iter()
+            .filter(|field| field.starts_with("show_"))
+        {
+            let var = format!("RUFETCH_{}", field.to_uppercase());
+            let shown = match env::var(&var) {
+                Ok(shown) => shown,
+                Err(_) => continue,
+            };
+            let shown = match shown.to_lowercase().as_str() {
+                "1" | "true" => true,
+                "0" | "false" => false,
+                _ => {
+                    invalid.push(format!(
+                        "{} must be 1, 0, true or false, got `{}`, ignoring it",
+                        var, shown
+                    ));
+                    continue;
+                }
+            };
+            table.insert(field.to_string(), toml::Value::Boolean(shown));
+            found = true;
         }
+        found
+    }
 
-        if self.show_memory {
-            Config::print_mem(&self, &sys);
+    /// Returns the top-level keys of `value` that aren't options, serde skips them silently.
+    fn unknown_keys(value: &toml::Value) -> Vec<String> {
+        let fields = Config::field_names();
+        match value.as_table() {
+            Some(table) => table
+                .keys()
+                .filter(|key| !fields.contains(&key.as_str()))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
         }
+    }
+
+    /// Returns the names of the fields a config file may set.
+    fn field_names() -> &'static [&'static str] {
+        let mut fields = None;
+        let _ = Config::deserialize(FieldNames(&mut fields));
+        fields.unwrap_or_default()
+    }
+
+    /// Returns `<config_dir>/ru_fetch/config.toml`, or `None` if there is no config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("ru_fetch").join("config.toml"))
+    }
 
-        if self.show_swap {
-            Config::print_swap(&self, &sys);
+    /// Writes the default config, with comments describing each option, to `path`.
+    ///
+    /// # Errors
+    /// Fails with [io::ErrorKind::AlreadyExists] if the file exists and `force` isn't set.
+    pub fn write_default(path: &Path, force: bool) -> io::Result<()> {
+        if path.exists() && !force {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists, pass --force to overwrite it",
+                    path.display()
+                ),
+            ));
         }
 
-        if self.show_temperature {
-            Config::print_temps(&sys);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
         }
+        fs::write(path, DEFAULT_CONFIG.trim_start())
+    }
 
-        if self.show_colors {
-            Config::print_colors(&self);
+    /// Returns the [RefreshKind] covering only the information the enabled fields need,
+    /// so disabled fields don't cost any collection time.
+    pub fn refresh_kind(&self) -> RefreshKind {
+        let mut kind = RefreshKind::new();
+
+        if self.shows("memory") || self.shows("swap") {
+            kind = kind.with_memory();
+        }
+        if self.shows("cpu") {
+            kind = kind.with_cpu(CpuRefreshKind::everything());
+        }
+        if self.shows("disks") {
+            kind = kind.with_disks_list();
+        }
+        if self.shows("temperature") || self.shows("cpu_temp") {
+            kind = kind.with_components_list();
+        }
+        // Listing processes is slow, so only do it for the modules that need them
+        if self.shows("terminal") || self.shows("processes") {
+            kind = kind.with_processes(ProcessRefreshKind::new());
         }
+        if self.shows("network") {
+            kind = kind.with_networks().with_networks_list();
+        }
+
+        kind
     }
 
-    fn print_hostname(host_name: Option<String>) {
-        if let Some(host_name) = &host_name {
-            println!("{} {}", Blue.bold().paint("Host:"), *host_name);
+    /// Takes the second CPU sample needed to compute usage, if usage is shown at all.
+    /// Usage is computed from the difference between two refreshes, so this sleeps
+    /// for [CPU_SAMPLE_INTERVAL].
+    pub fn sample_cpu_usage(&self, sys: &mut System) {
+        if self.shows("cpu") && (self.show_cpu_usage || self.show_cores && self.show_per_core_usage)
+        {
+            sys.refresh_cpu();
+            thread::sleep(CPU_SAMPLE_INTERVAL);
+            sys.refresh_cpu();
         }
     }
 
-    fn print_os(&self, sys: &System) {
-        let os = sys.long_os_version();
+    /// Checks the values serde can't, resetting invalid ones to their defaults.
+    /// Returns a warning for every value that was reset.
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut warnings: Vec<String> = self
+            .unknown_keys
+            .iter()
+            .map(|key| format!("unknown config key `{}`, ignoring it", key))
+            .chain(self.invalid_env.iter().cloned())
+            .collect();
+
+        if parse_color(&self.label_color).is_none() {
+            warnings.push(format!(
+                "unknown label_color `{}`, using blue",
+                self.label_color
+            ));
+            self.label_color = default_label_color();
+        }
 
-        if let Some(os) = &os {
-            println!("{} {}", Blue.bold().paint("OS:"), os);
+        if let Some(value_color) = &self.value_color {
+            if parse_color(value_color).is_none() {
+                warnings.push(format!(
+                    "unknown value_color `{}`, leaving values uncolored",
+                    value_color
+                ));
+                self.value_color = None;
+            }
         }
-    }
 
-    fn print_uptime(&self, sys: &System) {
-        match &self.uptime_type {
-            Time::Second => {
-                let uptime_sec = sys.uptime();
-                println!("{} {:.2} sec(s)", Blue.bold().paint("Uptime: "), uptime_sec);
+        if let Some(theme) = &self.theme {
+            if Config::theme(theme).is_none() {
+                warnings.push(format!(
+                    "unknown theme `{}`, expected one of `default`, `mono`, `minimal`",
+                    theme
+                ));
+                self.theme = None;
             }
-            Time::Minute => {
-                let uptime_min: f64 = sys.uptime() as f64 / 60 as f64;
-                println!("{} {:.2} min(s)", Blue.bold().paint("Uptime:"), uptime_min);
+        }
+
+        if let Some(palette) = &mut self.color_palette {
+            palette.retain(|color| {
+                let known = parse_color(color).is_some();
+                if !known {
+                    warnings.push(format!(
+                        "unknown color `{}` in color_palette, skipping it",
+                        color
+                    ));
+                }
+                known
+            });
+        }
+
+        if let Some(names) = &mut self.modules {
+            names.retain(|name| {
+                let known = MODULE_NAMES.contains(&name.as_str());
+                if !known {
+                    warnings.push(format!("unknown module `{}`, skipping it", name));
+                }
+                known
+            });
+        }
+
+        for key in self.labels.keys() {
+            let extra = ["disk_total", "container"];
+            if !MODULE_NAMES.contains(&key.as_str()) && !extra.contains(&key.as_str()) {
+                warnings.push(format!("unknown field `{}` in [labels]", key));
             }
-            Time::Hour => {
-                let uptime_hour: f64 = sys.uptime() as f64 / 3600 as f64;
-                println!(
-                    "{} {:.2} hour(s)",
-                    Blue.bold().paint("Uptime: "),
-                    uptime_hour
-                );
+        }
+
+        for name in self.icons.keys() {
+            if !MODULE_NAMES.contains(&name.as_str()) {
+                warnings.push(format!("unknown module `{}` in [icons]", name));
             }
         }
-    }
 
-    fn print_kernel_ver(sys: &System) {
-        let kernel_ver = sys.kernel_version();
-        if let Some(kernel_ver) = &kernel_ver {
-            println!("{} {}", Blue.bold().paint("Kernel Version:"), *kernel_ver);
+        if self.disk_warn_percent > self.disk_crit_percent {
+            warnings.push(format!(
+                "disk_warn_percent ({}) is above disk_crit_percent ({}), using 70 and 90",
+                self.disk_warn_percent, self.disk_crit_percent
+            ));
+            self.disk_warn_percent = default_disk_warn_percent();
+            self.disk_crit_percent = default_disk_crit_percent();
         }
-    }
 
-    fn print_cpu(&self, sys: &System) {
-        let cpu_str = format!(
-            "{} {}",
-            Blue.bold().paint("CPU:"),
-            sys.global_cpu_info().brand()
-        );
+        if self.temp_warn > self.temp_crit {
+            warnings.push(format!(
+                "temp_warn ({}) is above temp_crit ({}), using 70 and 85",
+                self.temp_warn, self.temp_crit
+            ));
+            self.temp_warn = default_temp_warn();
+            self.temp_crit = default_temp_crit();
+        }
 
-        if *&self.show_cores {
-            println!("{} ({})", cpu_str, sys.cpus().len());
+        warnings
+    }
+
+    /// Paints `text` with `style`, or returns it as is when colored output is off.
+    pub fn paint<S: AsRef<str>>(&self, style: Style, text: S) -> String {
+        if self.colored_output {
+            style.paint(text.as_ref()).to_string()
         } else {
-            println!("{}", cpu_str);
+            text.as_ref().to_string()
         }
     }
 
-    fn print_disks(sys: &System) {
-        for disk in sys.disks() {
-            println!(
-                "{}: {} ({:.2} GB / {:.2} GB)",
-                Blue.bold().paint("Disk"),
-                Yellow.bold().paint(disk.name().to_string_lossy()),
-                (disk.total_space() - disk.available_space()) as f64 / (1024.0 * 1024.0 * 1024.0),
-                disk.total_space() as f64 / (1024 * 1024 * 1024) as f64
-            )
+    /// Returns the `[labels]` entry for `key`, or `default` if there is none.
+    pub fn label(&self, key: &str, default: &str) -> String {
+        self.labels
+            .get(key)
+            .map_or(default, |label| label.trim_end_matches(':'))
+            .to_string()
+    }
+
+    /// Paints `text` in the configured label color.
+    pub fn paint_label<S: AsRef<str>>(&self, text: S) -> String {
+        let color = parse_color(&self.label_color).unwrap_or(Blue);
+        self.paint(color.bold(), text)
+    }
+
+    /// Paints `text` in the configured value color, if any.
+    pub fn paint_value<S: AsRef<str>>(&self, text: S) -> String {
+        match self.value_color.as_deref().and_then(parse_color) {
+            Some(color) => self.paint(color.normal(), text),
+            None => text.as_ref().to_string(),
         }
     }
 
-    fn print_mem(&self, sys: &System) {
-        match &self.memory_type {
-            MemType::KB => println!(
-                "{} {:.2} KB / {:.2} KB",
-                Blue.bold().paint("Memory:"),
-                sys.used_memory() as f64 / 1e+3,
-                sys.total_memory() as f64 / 1e+3
+    /// Renders `used` out of `total` as a bar like `[██████░░░░] 60%`, `width` cells wide.
+    /// The bar is colored by [usage_color]. Returns `None` if `total` is zero.
+    pub fn render_bar(
+        &self,
+        used: u64,
+        total: u64,
+        width: usize,
+        thresholds: (u8, u8),
+    ) -> Option<String> {
+        if total == 0 {
+            return None;
+        }
+
+        let ratio = (used as f64 / total as f64).min(1.0);
+        let filled = (ratio * width as f64).round() as usize;
+
+        Some(format!(
+            "[{}{}] {:.0}%",
+            self.paint(
+                usage_color(ratio * 100.0, thresholds).normal(),
+                "█".repeat(filled)
             ),
-            MemType::MB => {
-                println!(
-                    "{} {:.2} MB / {:.2} MB",
-                    Blue.bold().paint("Memory:"),
-                    sys.used_memory() as f64 / 1e+6,
-                    sys.total_memory() as f64 / 1e+6
-                )
-            }
-            MemType::GB => {
-                println!(
-                    "{} {:.2} GB / {:.2} GB",
-                    Blue.bold().paint("Memory:"),
-                    sys.used_memory() as f64 / 1e+9,
-                    sys.total_memory() as f64 / 1e+9
+            "░".repeat(width - filled),
+            ratio * 100.0
+        ))
+    }
+
+    /// Prints every module name and whether the loaded config enables it, in plain text.
+    pub fn print_modules(&self) {
+        let width = MODULE_NAMES
+            .iter()
+            .map(|name| name.len())
+            .max()
+            .unwrap_or(0);
+        let list: String = MODULE_NAMES
+            .iter()
+            .map(|name| {
+                let state = if self.shows(name) {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                format!("{:width$}  {}\n", name, state, width = width)
+            })
+            .collect();
+        let _ = io::stdout().lock().write_all(list.as_bytes());
+    }
+
+    /// Loads the logo from `logo_path`, or the built-in one if it isn't set.
+    /// Warns and falls back to the built-in logo if the file can't be read.
+    pub fn logo(&self) -> Logo {
+        let path = match &self.logo_path {
+            Some(path) => path,
+            None => return Logo::detect(),
+        };
+
+        Logo::from_file(path).unwrap_or_else(|error| {
+            eprintln!(
+                "{}",
+                self.paint(
+                    Yellow.bold(),
+                    format!(
+                        "Could not read the logo at {}, using the default logo: {}",
+                        path.display(),
+                        error
+                    )
                 )
-            }
+            );
+            Logo::detect()
+        })
+    }
+
+    /// Returns the enabled modules in the order they are printed.
+    pub fn enabled_modules(&self) -> Vec<(&str, Box<dyn Module>)> {
+        let names: Vec<&str> = match &self.modules {
+            Some(names) => names.iter().map(String::as_str).collect(),
+            None => MODULE_NAMES
+                .iter()
+                .copied()
+                .filter(|name| self.show_flag(name))
+                .collect(),
+        };
+
+        names
+            .into_iter()
+            .filter_map(|name| Some((name, module_by_name(name)?)))
+            .collect()
+    }
+
+    /// Turns on every module in the usual order, replacing the `modules` list, along with
+    /// the options that add details to them, e.g. `show_arch` or `show_disk_fs`.
+    /// Options that only change the look, like `show_logo` or `force_swap`, keep their values.
+    pub fn show_everything(&mut self) {
+        self.modules = Some(MODULE_NAMES.iter().map(|name| name.to_string()).collect());
+        for flag in [
+            &mut self.show_arch,
+            &mut self.show_cores,
+            &mut self.show_threads,
+            &mut self.show_cpu_usage,
+            &mut self.show_cpu_freq,
+            &mut self.show_cpu_codename,
+            &mut self.show_disk_mount,
+            &mut self.show_disk_fs,
+            &mut self.show_disk_total,
+            &mut self.show_memory_percent,
+            &mut self.memory_detailed,
+        ] {
+            *flag = true;
         }
     }
 
-    fn print_swap(&self, sys: &System) {
-        sys.global_cpu_info().brand();
-        match &self.memory_type {
-            MemType::KB => println!(
-                "{} {:.2} KB / {:.2} KB",
-                Blue.bold().paint("Swap:"),
-                sys.used_swap() as f64 / 1e+3,
-                sys.total_swap() as f64 / 1e+3
-            ),
-            MemType::MB => {
-                println!(
-                    "{} {:.2} MB / {:.2} MB",
-                    Blue.bold().paint("Swap:"),
-                    sys.used_swap() as f64 / 1e+6,
-                    sys.total_swap() as f64 / 1e+6
-                )
-            }
-            MemType::GB => {
-                println!(
-                    "{} {:.2} GB / {:.2} GB",
-                    Blue.bold().paint("Swap:"),
-                    sys.used_swap() as f64 / 1e+9,
-                    sys.total_swap() as f64 / 1e+9
-                )
-            }
+    /// Limits the printed modules to `fields`, whatever their `show_*` flags.
+    /// They keep the order of the `modules` list if it is set and the usual order otherwise,
+    /// so fields left out of the `modules` list stay hidden.
+    pub fn select_fields(&mut self, fields: &[String]) {
+        let order: Vec<String> = match &self.modules {
+            Some(names) => names.clone(),
+            None => MODULE_NAMES.iter().map(|name| name.to_string()).collect(),
+        };
+        self.modules = Some(
+            order
+                .into_iter()
+                .filter(|name| fields.contains(name))
+                .collect(),
+        );
+    }
+
+    /// Returns the number of columns a line may take up: `max_width` if set, otherwise
+    /// the terminal's width. `None` if there's no limit, e.g. when piping.
+    pub fn line_width(&self) -> Option<usize> {
+        self.max_width.or_else(terminal_width)
+    }
+
+    /// Returns the icon put before the labels of the module called `name`, if icons are on.
+    pub fn icon(&self, name: &str) -> Option<&str> {
+        if !self.show_icons {
+            return None;
         }
+        self.icons
+            .get(name)
+            .map(String::as_str)
+            .or_else(|| default_icon(name))
     }
 
-    fn print_colors(&self) {
-        for _ in 0..self.colors_height {
-            println!(
-                "{}{}{}{}",
-                Red.on(Red)
-                    .paint(format!("{:width$}", width = &self.colors_width * 2 + 1)),
-                Green
-                    .on(Green)
-                    .paint(format!("{:width$}", width = &self.colors_width * 2 + 1)),
-                Blue.on(Blue)
-                    .paint(format!("{:width$}", width = &self.colors_width * 2 + 1)),
-                Yellow
-                    .on(Yellow)
-                    .paint(format!("{:width$}", width = &self.colors_width * 2 + 1))
-            );
+    /// Whether the module called `name` is shown, by the `modules` list if it is set
+    /// and by the module's `show_*` flag otherwise.
+    pub fn shows(&self, name: &str) -> bool {
+        match &self.modules {
+            Some(names) => names.iter().any(|shown| shown == name),
+            None => self.show_flag(name),
         }
-        for _ in 0..self.colors_height {
-            println!(
-                "{}{}{}{}",
-                Black
-                    .on(Black)
-                    .paint(format!("{:width$}", width = &self.colors_width * 2 + 1)),
-                White
-                    .on(White)
-                    .paint(format!("{:width$}", width = &self.colors_width * 2 + 1)),
-                Purple
-                    .on(Purple)
-                    .paint(format!("{:width$}", width = &self.colors_width * 2 + 1)),
-                Cyan.on(Cyan)
-                    .paint(format!("{:width$}", width = &self.colors_width * 2 + 1))
-            );
+    }
+
+    /// Returns the `show_*` flag of the module called `name`.
+    fn show_flag(&self, name: &str) -> bool {
+        match name {
+            "title" => self.show_title.unwrap_or(self.show_hostname),
+            "host" => self.show_host.unwrap_or(self.show_hostname),
+            "model" => self.show_model,
+            "virt" => self.show_virt,
+            "os" => self.show_os,
+            "de" => self.show_de,
+            "wm" => self.show_wm,
+            "display_server" => self.show_display_server,
+            "packages" => self.show_packages,
+            "shell" => self.show_shell,
+            "terminal" => self.show_terminal,
+            "locale" => self.show_locale,
+            "uptime" => self.show_uptime,
+            "boot_time" => self.show_boot_time,
+            "datetime" => self.show_datetime,
+            "timezone" => self.show_timezone,
+            "load_avg" => self.show_load_avg,
+            "processes" => self.show_processes,
+            "kernel" => self.show_kernel_version,
+            "init" => self.show_init,
+            "disks" => self.show_disks,
+            "cpu" => self.show_cpu,
+            "cpu_temp" => self.show_cpu_temp,
+            "cpu_cache" => self.show_cpu_cache,
+            "gpu" => self.show_gpu,
+            "resolution" => self.show_resolution,
+            "memory" => self.show_memory,
+            "swap" => self.show_swap,
+            "local_ip" => self.show_local_ip,
+            "network" => self.show_network,
+            "battery" => self.show_battery,
+            "temperature" => self.show_temperature,
+            "colors" => self.show_colors,
+            _ => false,
         }
     }
+}
 
-    fn print_temps(sys: &System) {
-        println!();
-        println!("{}", Red.bold().paint("Temperature"));
-        println!(
-            "{}",
-            Red.bold().paint(repeat('-').take(20).collect::<String>())
-        );
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        for component in sys.components() {
-            println!(
-                "{}: {}°C",
-                Blue.bold().paint(component.label()),
-                component.temperature()
-            );
-        }
-        println!();
+    /// The default config as `--gen-config` writes it, with `theme` set.
+    fn generated_with_theme(theme: &str) -> Config {
+        let contents =
+            DEFAULT_CONFIG.replace("# theme = \"default\"", &format!("theme = \"{}\"", theme));
+        Config::parse(Path::new("config.toml"), &contents).expect("the themed config is valid")
     }
 
-    fn get_user() -> String {
-        let mut user_out = if cfg!(target_os = "windows") || cfg!(target_os = "linux") {
-            // linux, windows
-            Command::new("whoami").output().unwrap()
-        } else {
-            // darwin(mac)
-            Command::new("id -un").output().expect("none")
-        };
-        let user: String = if (str::from_utf8(&user_out.stdout).unwrap()).ends_with("\n") {
-            user_out.stdout.pop();
-            str::from_utf8(&user_out.stdout).unwrap().to_string()
-        } else {
-            str::from_utf8(&user_out.stdout).unwrap().to_string()
-        };
-        user
+    #[test]
+    fn mono_theme_uncolors_a_generated_config() {
+        let config = generated_with_theme("mono");
+        assert!(!config.colored_output);
+        assert_eq!(config.paint_label("OS:"), "OS:");
+        assert_eq!(config.paint(Red.bold(), "Temperature"), "Temperature");
     }
 
-    /// --------------- Linux only --------------------
-    ///
-    /// Gets the current desktop enviroment. The information might not be 100% accurate.
-    /// Skips if nothing useful is found.
-    fn print_desktop_environment() {
-        if cfg!(target_os = "linux") {
-            // linux
-            match option_env!("DESKTOP_SESSION") {
-                Some(val) => {
-                    let de_str = format!("{} {}", Blue.bold().paint("DE:"), val);
-
-                    println!("{}", de_str);
-                }
-                None => {}
-            }
-        } else {
-            {}
-        };
+    #[test]
+    fn minimal_theme_applies_to_a_generated_config() {
+        let config = generated_with_theme("minimal");
+        assert_eq!(config.separator_length, 0);
+        assert!(config.colors_compact);
+        assert!(config.colored_output);
+    }
+
+    #[test]
+    fn options_set_alongside_a_theme_win() {
+        let contents = "theme = \"mono\"\ncolored_output = true\n";
+        let config = Config::parse(Path::new("config.toml"), contents).unwrap();
+        assert!(config.colored_output);
+    }
+
+    #[test]
+    fn env_overrides_apply_to_the_fallback_config() {
+        let broken = env::temp_dir().join(format!("rufetch-broken-{}.toml", std::process::id()));
+        fs::write(&broken, "show_os = \n").unwrap();
+        env::set_var("RUFETCH_SHOW_OS", "0");
+        env::set_var("RUFETCH_SHOW_TEMPERATURE", "true");
+
+        let loaded = Config::new(Some(broken.clone()));
+        let fallback = Config::fallback();
+        env::remove_var("RUFETCH_SHOW_OS");
+        env::remove_var("RUFETCH_SHOW_TEMPERATURE");
+        let _ = fs::remove_file(&broken);
+
+        assert!(loaded.is_err());
+        assert!(!fallback.show_os);
+        assert!(fallback.show_temperature);
     }
 }
@@ -0,0 +1,126 @@
+#[cfg(unix)]
+use std::ffi::CStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A point in time in the local timezone.
+pub struct DateTime {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// The timezone's name, e.g. `CET`. Empty if it is unknown.
+    pub zone: String,
+}
+
+impl DateTime {
+    /// Returns the current local time.
+    pub fn now() -> Option<DateTime> {
+        let epoch = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        DateTime::local(epoch as i64)
+    }
+
+    /// Converts seconds since the unix epoch to the local time.
+    #[cfg(unix)]
+    pub fn local(epoch: i64) -> Option<DateTime> {
+        let time = epoch as libc::time_t;
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+            return None;
+        }
+
+        Some(DateTime {
+            year: tm.tm_year + 1900,
+            month: tm.tm_mon as u32 + 1,
+            day: tm.tm_mday as u32,
+            hour: tm.tm_hour as u32,
+            minute: tm.tm_min as u32,
+            second: tm.tm_sec as u32,
+            zone: if tm.tm_zone.is_null() {
+                String::new()
+            } else {
+                unsafe { CStr::from_ptr(tm.tm_zone) }
+                    .to_string_lossy()
+                    .into_owned()
+            },
+        })
+    }
+
+    /// Converts seconds since the unix epoch to the local time.
+    #[cfg(windows)]
+    pub fn local(epoch: i64) -> Option<DateTime> {
+        use winapi::shared::minwindef::FILETIME;
+        use winapi::um::minwinbase::SYSTEMTIME;
+        use winapi::um::timezoneapi::{
+            FileTimeToSystemTime, GetTimeZoneInformation, SystemTimeToTzSpecificLocalTime,
+            TIME_ZONE_INFORMATION,
+        };
+        use winapi::um::winnt::TIME_ZONE_ID_DAYLIGHT;
+
+        // FILETIME counts 100ns intervals since 1601-01-01.
+        let ticks = (epoch as u64 + 11_644_473_600) * 10_000_000;
+        let file_time = FILETIME {
+            dwLowDateTime: ticks as u32,
+            dwHighDateTime: (ticks >> 32) as u32,
+        };
+        let mut utc: SYSTEMTIME = unsafe { std::mem::zeroed() };
+        let mut local: SYSTEMTIME = unsafe { std::mem::zeroed() };
+        unsafe {
+            if FileTimeToSystemTime(&file_time, &mut utc) == 0
+                || SystemTimeToTzSpecificLocalTime(std::ptr::null(), &utc, &mut local) == 0
+            {
+                return None;
+            }
+        }
+
+        // Windows only knows the long names, e.g. "W. Europe Standard Time".
+        let mut info: TIME_ZONE_INFORMATION = unsafe { std::mem::zeroed() };
+        let name = match unsafe { GetTimeZoneInformation(&mut info) } {
+            TIME_ZONE_ID_DAYLIGHT => &info.DaylightName[..],
+            _ => &info.StandardName[..],
+        };
+        let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+
+        Some(DateTime {
+            year: i32::from(local.wYear),
+            month: u32::from(local.wMonth),
+            day: u32::from(local.wDay),
+            hour: u32::from(local.wHour),
+            minute: u32::from(local.wMinute),
+            second: u32::from(local.wSecond),
+            zone: String::from_utf16_lossy(&name[..len]),
+        })
+    }
+
+    /// Formats the time with strftime-style `%Y`, `%m`, `%d`, `%H`, `%M`, `%S`, `%Z` and `%%`.
+    /// Anything else is copied as is.
+    pub fn format(&self, format: &str) -> String {
+        let mut formatted = String::new();
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                formatted.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => formatted.push_str(&self.year.to_string()),
+                Some('m') => formatted.push_str(&format!("{:02}", self.month)),
+                Some('d') => formatted.push_str(&format!("{:02}", self.day)),
+                Some('H') => formatted.push_str(&format!("{:02}", self.hour)),
+                Some('M') => formatted.push_str(&format!("{:02}", self.minute)),
+                Some('S') => formatted.push_str(&format!("{:02}", self.second)),
+                Some('Z') => formatted.push_str(&self.zone),
+                Some('%') => formatted.push('%'),
+                Some(other) => {
+                    formatted.push('%');
+                    formatted.push(other);
+                }
+                None => formatted.push('%'),
+            }
+        }
+
+        formatted
+    }
+}
@@ -0,0 +1,448 @@
+use std::fmt;
+
+/// A minimal JSON value, used for the `--json` output and JSON or YAML config files.
+#[derive(Clone, Debug)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Builds an object from key/value pairs, keeping their order.
+    pub fn object(pairs: Vec<(&str, Value)>) -> Value {
+        Value::Object(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+}
+
+impl Value {
+    /// Converts the value to TOML, which has no null, so nulls and the keys holding them
+    /// are dropped. Whole numbers become integers.
+    pub fn into_toml(self) -> Option<toml::Value> {
+        Some(match self {
+            Value::Null => return None,
+            Value::Bool(value) => toml::Value::Boolean(value),
+            Value::Number(value) if value.fract() == 0.0 && value.abs() < i64::MAX as f64 => {
+                toml::Value::Integer(value as i64)
+            }
+            Value::Number(value) => toml::Value::Float(value),
+            Value::String(value) => toml::Value::String(value),
+            Value::Array(values) => {
+                toml::Value::Array(values.into_iter().filter_map(Value::into_toml).collect())
+            }
+            Value::Object(pairs) => toml::Value::Table(
+                pairs
+                    .into_iter()
+                    .filter_map(|(key, value)| Some((key, value.into_toml()?)))
+                    .collect(),
+            ),
+        })
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        Value::Number(value as f64)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::Number(value as f64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Number(f64::from(value))
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Self {
+        Value::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(value) => write!(f, "{}", value),
+            // JSON has no NaN or infinity.
+            Value::Number(value) if !value.is_finite() => write!(f, "null"),
+            Value::Number(value) => write!(f, "{}", value),
+            Value::String(value) => write_str(f, value),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(pairs) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Flattens the value into `key=value` lines, one per scalar and each ending in a
+    /// newline. Keys of nested objects and arrays are joined with `_`, e.g. `disks_0_name`,
+    /// and nulls are left out. Strings are written as is, with line breaks as spaces.
+    pub fn to_key_values(&self) -> String {
+        let mut lines = String::new();
+        self.write_key_values("", &mut lines);
+        lines
+    }
+
+    fn write_key_values(&self, key: &str, lines: &mut String) {
+        let nested = |child: &str| {
+            if key.is_empty() {
+                child.to_string()
+            } else {
+                format!("{}_{}", key, child)
+            }
+        };
+
+        let value = match self {
+            Value::Null => return,
+            Value::Number(value) if !value.is_finite() => return,
+            Value::Bool(value) => value.to_string(),
+            Value::Number(value) => value.to_string(),
+            Value::String(value) => value.replace(['\r', '\n'], " "),
+            Value::Array(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    value.write_key_values(&nested(&i.to_string()), lines);
+                }
+                return;
+            }
+            Value::Object(pairs) => {
+                for (child, value) in pairs {
+                    value.write_key_values(&nested(child), lines);
+                }
+                return;
+            }
+        };
+        lines.push_str(&format!("{}={}\n", key, value));
+    }
+}
+
+/// Writes `value` as a quoted JSON string, escaping as needed.
+fn write_str(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl Value {
+    /// Parses a JSON document.
+    ///
+    /// # Errors
+    /// Returns a message with the byte offset of the first syntax error.
+    pub fn parse(text: &str) -> Result<Value, String> {
+        let mut parser = Parser {
+            chars: text.char_indices().peekable(),
+            len: text.len(),
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(value),
+            Some((offset, _)) => Err(format!("unexpected trailing data at byte {}", offset)),
+        }
+    }
+}
+
+/// A recursive descent parser over the characters of a JSON document.
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    len: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    /// Returns an error naming what was expected at the current position.
+    fn expected<T>(&mut self, what: &str) -> Result<T, String> {
+        let offset = self.chars.peek().map_or(self.len, |(offset, _)| *offset);
+        Err(format!("expected {} at byte {}", what, offset))
+    }
+
+    /// Consumes `c` after any whitespace.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        self.chars.next_if(|(_, next)| *next == c).is_some()
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.chars.peek().map(|(_, c)| *c) {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some('t') => self.keyword("true", Value::Bool(true)),
+            Some('f') => self.keyword("false", Value::Bool(false)),
+            Some('n') => self.keyword("null", Value::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => self.expected("a value"),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        for expected in word.chars() {
+            if self.chars.next_if(|(_, c)| *c == expected).is_none() {
+                return self.expected(&format!("`{}`", word));
+            }
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let mut number = String::new();
+        while let Some((_, c)) = self
+            .chars
+            .next_if(|(_, c)| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            number.push(c);
+        }
+        match number.parse() {
+            Ok(number) => Ok(Value::Number(number)),
+            Err(_) => self.expected("a number"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if !self.eat('"') {
+            return self.expected("a string");
+        }
+
+        let mut string = String::new();
+        loop {
+            match self.chars.next().map(|(_, c)| c) {
+                Some('"') => return Ok(string),
+                Some('\\') => match self.chars.next().map(|(_, c)| c) {
+                    Some('"') => string.push('"'),
+                    Some('\\') => string.push('\\'),
+                    Some('/') => string.push('/'),
+                    Some('b') => string.push('\u{8}'),
+                    Some('f') => string.push('\u{c}'),
+                    Some('n') => string.push('\n'),
+                    Some('r') => string.push('\r'),
+                    Some('t') => string.push('\t'),
+                    Some('u') => match self.unicode_escape() {
+                        Some(c) => string.push(c),
+                        None => return self.expected("a \\u escape"),
+                    },
+                    _ => return self.expected("an escape sequence"),
+                },
+                Some(c) => string.push(c),
+                None => return self.expected("a closing `\"`"),
+            }
+        }
+    }
+
+    /// Decodes the digits of a `\u` escape, combining a UTF-16 surrogate pair such as
+    /// `\udb80\udf4b` into one character.
+    fn unicode_escape(&mut self) -> Option<char> {
+        let high = self.hex_digits()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high);
+        }
+
+        self.chars.next_if(|(_, c)| *c == '\\')?;
+        self.chars.next_if(|(_, c)| *c == 'u')?;
+        let low = self.hex_digits()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return None;
+        }
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+    }
+
+    /// Reads the four hex digits of a `\u` escape.
+    fn hex_digits(&mut self) -> Option<u32> {
+        let hex: String = (0..4)
+            .filter_map(|_| self.chars.next_if(|(_, c)| c.is_ascii_hexdigit()))
+            .map(|(_, c)| c)
+            .collect();
+        if hex.len() == 4 {
+            u32::from_str_radix(&hex, 16).ok()
+        } else {
+            None
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.eat('[');
+        let mut values = Vec::new();
+        if self.eat(']') {
+            return Ok(Value::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            if self.eat(']') {
+                return Ok(Value::Array(values));
+            }
+            if !self.eat(',') {
+                return self.expected("`,` or `]`");
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.eat('{');
+        let mut pairs = Vec::new();
+        if self.eat('}') {
+            return Ok(Value::Object(pairs));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            if !self.eat(':') {
+                return self.expected("`:`");
+            }
+            pairs.push((key, self.value()?));
+            if self.eat('}') {
+                return Ok(Value::Object(pairs));
+            }
+            if !self.eat(',') {
+                return self.expected("`,` or `}`");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_values_round_trip() {
+        let text = r#"{"os":{"name":"Arch","version":null},"cpus":[1,2.5,-3e2],"ok":true}"#;
+        assert_eq!(
+            Value::parse(text).unwrap().to_string(),
+            r#"{"os":{"name":"Arch","version":null},"cpus":[1,2.5,-300],"ok":true}"#
+        );
+        assert_eq!(Value::parse(" [ ] ").unwrap().to_string(), "[]");
+    }
+
+    #[test]
+    fn escapes() {
+        let value = Value::parse(r#""a\"b\\c\/d\n\t\u00e9""#).unwrap();
+        assert_eq!(value.to_string(), r#""a\"b\\c/d\n\té""#);
+    }
+
+    #[test]
+    fn surrogate_pairs_combine() {
+        let value = Value::parse(r#"{"icons":{"os":"\udb80\udf4b"}}"#).unwrap();
+        assert_eq!(value.to_string(), "{\"icons\":{\"os\":\"\u{f034b}\"}}");
+    }
+
+    #[test]
+    fn lone_surrogates_are_errors() {
+        assert_eq!(
+            Value::parse(r#""\udb80""#).unwrap_err(),
+            "expected a \\u escape at byte 7"
+        );
+        assert!(Value::parse(r#""\udb80\u0041""#).is_err());
+        assert!(Value::parse(r#""\udf4b""#).is_err());
+        assert!(Value::parse(r#""\u12""#).is_err());
+    }
+
+    #[test]
+    fn syntax_errors_name_the_byte() {
+        let error = |text| Value::parse(text).unwrap_err();
+        assert_eq!(error(r#"{"a" 1}"#), "expected `:` at byte 5");
+        assert_eq!(error("[1 2]"), "expected `,` or `]` at byte 3");
+        assert_eq!(error(r#"{"a":1,}"#), "expected a string at byte 7");
+        assert_eq!(error("tru"), "expected `true` at byte 3");
+        assert_eq!(error(r#""open"#), "expected a closing `\"` at byte 5");
+        assert_eq!(error(r#""\q""#), "expected an escape sequence at byte 3");
+        assert_eq!(error("1 2"), "unexpected trailing data at byte 2");
+        assert_eq!(error(""), "expected a value at byte 0");
+    }
+
+    #[test]
+    fn key_values_flatten_nested_keys() {
+        let value = Value::parse(r#"{"os":"Arch\nLinux","disks":[{"name":"sda"}],"gpu":null}"#);
+        assert_eq!(
+            value.unwrap().to_key_values(),
+            "os=Arch Linux\ndisks_0_name=sda\n"
+        );
+    }
+
+    #[test]
+    fn toml_drops_nulls_and_keeps_whole_numbers_integers() {
+        let value = Value::parse(r#"{"width":12,"ratio":0.5,"none":null,"list":[null,true]}"#);
+        let toml = value.unwrap().into_toml().unwrap();
+        assert_eq!(toml.get("width"), Some(&toml::Value::Integer(12)));
+        assert_eq!(toml.get("ratio"), Some(&toml::Value::Float(0.5)));
+        assert_eq!(toml.get("none"), None);
+        assert_eq!(
+            toml.get("list"),
+            Some(&toml::Value::Array(vec![toml::Value::Boolean(true)]))
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! RuFetch's system detection and rendering, as used by the `rufetch` binary.
+//!
+//! ```no_run
+//! use rufetch::sysinfo::{System, SystemExt};
+//! use rufetch::Config;
+//!
+//! let config = Config::default();
+//! let mut sys = System::new_with_specifics(config.refresh_kind());
+//! config.sample_cpu_usage(&mut sys);
+//! print!("{}", rufetch::render(&rufetch::gather(&config, &sys), &config));
+//! ```
+
+pub mod conf;
+pub mod datetime;
+pub mod json;
+pub mod logo;
+pub mod modules;
+pub mod types;
+mod yaml;
+
+pub use sysinfo;
+pub use types::Config;
+
+use json::Value;
+use modules::{Line, Module, Output};
+use std::thread;
+use sysinfo::System;
+
+/// Everything the enabled modules found, in print order.
+pub struct SystemInfo {
+    pub modules: Vec<ModuleInfo>,
+}
+
+/// What one module found.
+pub struct ModuleInfo {
+    /// The module's name, see [modules::MODULE_NAMES].
+    pub name: String,
+    /// The lines of the text output, empty if the module found nothing to show.
+    pub lines: Vec<Line>,
+    /// The raw values behind the lines, see [Output::values].
+    pub values: Vec<(&'static str, Value)>,
+}
+
+impl SystemInfo {
+    /// Collects the raw values of every module into a single JSON object.
+    pub fn values(&self) -> Value {
+        Value::object(
+            self.modules
+                .iter()
+                .flat_map(|module| module.values.iter())
+                .map(|(key, value)| (*key, value.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// Modules that spawn commands or wait on the network, run on threads of their own.
+const BACKGROUND_MODULES: &[&str] = &[
+    "virt",
+    "de",
+    "wm",
+    "packages",
+    "shell",
+    "cpu_cache",
+    "gpu",
+    "resolution",
+    "local_ip",
+    "temperature",
+];
+
+/// Runs every module enabled by `config` on `sys`, leaving out fields without a value.
+///
+/// The slow modules run on background threads while the others run on this one,
+/// the results keep the configured order either way.
+///
+/// `sys` should be refreshed with [Config::refresh_kind] and [Config::sample_cpu_usage]
+/// first, so the modules find the information they need.
+pub fn gather(config: &Config, sys: &System) -> SystemInfo {
+    thread::scope(|scope| {
+        let pending: Vec<(&str, Pending)> = config
+            .enabled_modules()
+            .into_iter()
+            .map(|(name, module)| {
+                if BACKGROUND_MODULES.contains(&name) {
+                    (
+                        name,
+                        Pending::Running(scope.spawn(move || module.gather(config, sys))),
+                    )
+                } else {
+                    (name, Pending::Waiting(module))
+                }
+            })
+            .collect();
+
+        SystemInfo {
+            modules: pending
+                .into_iter()
+                .map(|(name, pending)| {
+                    let Output { mut lines, values } = match pending {
+                        // A module that panicked has nothing to show.
+                        Pending::Running(running) => running.join().unwrap_or_default(),
+                        Pending::Waiting(module) => module.gather(config, sys),
+                    };
+                    // No module prints a label with nothing after it
+                    lines.retain(|line| !line.is_blank());
+                    ModuleInfo {
+                        name: name.to_string(),
+                        lines,
+                        values,
+                    }
+                })
+                .collect(),
+        }
+    })
+}
+
+/// A module of [gather] that is either running on its own thread or yet to run.
+enum Pending<'scope> {
+    Running(thread::ScopedJoinHandle<'scope, Output>),
+    Waiting(Box<dyn Module>),
+}
+
+/// Renders `info` as the binary prints it, with icons, aligned labels, colors and
+/// the logo as configured. Lines are cut to [Config::line_width] and end in a newline.
+pub fn render(info: &SystemInfo, config: &Config) -> String {
+    let lines: Vec<Line> = info
+        .modules
+        .iter()
+        .flat_map(|module| {
+            let icon = config.icon(&module.name);
+            module.lines.iter().map(move |line| match (line, icon) {
+                (Line::Field(label, value), Some(icon)) => {
+                    Line::Field(format!("{} {}", icon, label), value.clone())
+                }
+                (line, _) => line.clone(),
+            })
+        })
+        .collect();
+
+    // Pad every label to the longest one shown, so the values form a column.
+    let width = if config.align_labels {
+        lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Field(label, _) => Some(label.chars().count()),
+                Line::Raw(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    let mut output: Vec<String> = lines
+        .into_iter()
+        .map(|line| match line {
+            Line::Field(label, value) => format!(
+                "{}{} {}",
+                config.paint_label(format!("{}:", label)),
+                " ".repeat(width.saturating_sub(label.chars().count())),
+                config.paint_value(value)
+            ),
+            Line::Raw(text) => text,
+        })
+        .collect();
+
+    if config.show_logo {
+        output = config.logo().beside(config, output);
+    }
+
+    let width = config.line_width();
+    output
+        .into_iter()
+        .map(|line| match width {
+            Some(width) => modules::truncate(&line, width) + "\n",
+            None => line + "\n",
+        })
+        .collect()
+}
+
+/// Renders the values of `info` on a single line, separated by ` | ` and ending in a
+/// newline, e.g. for status bars. Labels are left out, and so are raw lines such as the
+/// title, the temperature section and the color blocks, which don't fit on one line.
+/// The line is cut to [Config::line_width].
+pub fn render_oneline(info: &SystemInfo, config: &Config) -> String {
+    let values: Vec<String> = info
+        .modules
+        .iter()
+        .flat_map(|module| {
+            let icon = config.icon(&module.name);
+            module.lines.iter().filter_map(move |line| match line {
+                Line::Field(_, value) => Some(match icon {
+                    Some(icon) => format!("{} {}", icon, config.paint_value(value)),
+                    None => config.paint_value(value),
+                }),
+                Line::Raw(_) => None,
+            })
+        })
+        .collect();
+
+    let line = values.join(" | ");
+    match config.line_width() {
+        Some(width) => modules::truncate(&line, width) + "\n",
+        None => line + "\n",
+    }
+}
+
+/// Renders the raw values of `info` as a single JSON object, followed by a newline.
+///
+/// Sizes are in bytes and durations in seconds, so they don't depend on the configured units.
+pub fn render_json(info: &SystemInfo) -> String {
+    format!("{}\n", info.values())
+}
+
+/// Renders the values of [render_json] as `key=value` lines for shell scripts,
+/// see [Value::to_key_values].
+pub fn render_kv(info: &SystemInfo) -> String {
+    info.values().to_key_values()
+}
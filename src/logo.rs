@@ -0,0 +1,148 @@
+use crate::modules::{strip_ansi, Os};
+use crate::types::Config;
+use ansi_term::Color::{self, *};
+
+use std::{fs, io, path::Path};
+
+const ARCH: &str = r#"
+      /\
+     /  \
+    /\   \
+   /      \
+  /   ,,   \
+ /   |  |  -\
+/_-''    ''-_\
+"#;
+
+const UBUNTU: &str = r#"
+         _
+     ---(_)
+ _/  ---  \
+(_) |   |
+  \  --- _/
+     ---(_)
+"#;
+
+const DEBIAN: &str = r#"
+  _____
+ /  __ \
+|  /    |
+|  \___-
+-_
+  --_
+"#;
+
+const FEDORA: &str = r#"
+      _____
+     /   __)\
+     |  /  \ \
+  ___|  |__/ /
+ / (_    _)_/
+/ /  |  |
+\ \__/  |
+ \(_____/
+"#;
+
+const WINDOWS: &str = r#"
+######  ######
+######  ######
+######  ######
+
+######  ######
+######  ######
+######  ######
+"#;
+
+const MACOS: &str = r#"
+        .:'
+    __ :'__
+ .'`  `-'  ``.
+:          .-'
+:         :
+ :         `-;
+  `.__.-.__.'
+"#;
+
+const TUX: &str = r#"
+    ___
+   (.. |
+   (<> |
+  / __  \
+ ( /  \ /|
+_/\ __)/_)
+\/-____\/
+"#;
+
+/// Space between the logo and the info next to it.
+const GAP: usize = 3;
+
+/// The ASCII art printed to the left of the info.
+pub struct Logo {
+    lines: Vec<String>,
+    /// Built-in logos are painted in one color, custom ones bring their own.
+    color: Option<Color>,
+}
+
+impl Logo {
+    /// Picks the built-in logo of the running OS, from the `/etc/os-release` `ID` on Linux.
+    /// Unknown distros get Tux.
+    pub fn detect() -> Logo {
+        let (art, color) = if cfg!(target_os = "windows") {
+            (WINDOWS, Blue)
+        } else if cfg!(target_os = "macos") {
+            (MACOS, Green)
+        } else {
+            match Os::release_field("ID").as_deref() {
+                Some("arch") => (ARCH, Cyan),
+                Some("ubuntu") => (UBUNTU, Red),
+                Some("debian") => (DEBIAN, Red),
+                Some("fedora") => (FEDORA, Blue),
+                _ => (TUX, White),
+            }
+        };
+
+        Logo {
+            lines: art.trim_matches('\n').lines().map(String::from).collect(),
+            color: Some(color),
+        }
+    }
+
+    /// Reads a logo from a text file, keeping any ANSI colors in it.
+    pub fn from_file(path: &Path) -> io::Result<Logo> {
+        let art = fs::read_to_string(path)?;
+        Ok(Logo {
+            lines: art.trim_end().lines().map(String::from).collect(),
+            color: None,
+        })
+    }
+
+    /// Places the logo to the left of `info`, padding every line to the widest logo line.
+    pub fn beside(&self, config: &Config, info: Vec<String>) -> Vec<String> {
+        // Escape sequences take no space on screen, so they don't count towards the width.
+        let lines: Vec<String> = if config.colored_output {
+            self.lines.clone()
+        } else {
+            self.lines.iter().map(|line| strip_ansi(line)).collect()
+        };
+        let widths: Vec<usize> = lines
+            .iter()
+            .map(|line| strip_ansi(line).chars().count())
+            .collect();
+        let width = widths.iter().copied().max().unwrap_or(0);
+        let height = lines.len().max(info.len());
+        let mut info = info.into_iter();
+
+        (0..height)
+            .map(|i| {
+                let art = lines.get(i).map_or("", String::as_str);
+                let padding = " ".repeat(width - widths.get(i).unwrap_or(&0) + GAP);
+                let art = match self.color {
+                    Some(color) if !art.is_empty() => config.paint(color.bold(), art),
+                    _ => art.to_string(),
+                };
+                let line = format!("{}{}{}", art, padding, info.next().unwrap_or_default());
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+}
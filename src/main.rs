@@ -1,19 +1,182 @@
+use std::io::{self, Write};
+use std::time::Instant;
+use std::{env, fs, process, thread};
 use sysinfo::{System, SystemExt};
 
-// TODO: Add Gpu, Shell, Terminal, Resolution
-mod conf;
-mod types;
-use types::Config;
+mod args;
+use ansi_term::{
+    Color::{Blue, Red, Yellow},
+    Style,
+};
+use args::{Args, Format};
+use rufetch::types::{Config, ConfigError, Traffic};
 
 fn main() {
-    // Get the system info
-    let sys = System::new_all();
-
     // Enable color support for WIN10
     #[cfg(windows)]
     let _enabled = ansi_term::enable_ansi_support();
 
-    let config = Config::new();
+    let args = Args::parse().unwrap_or_else(|error| {
+        eprintln!("{}", paint(!no_color_env(), Red.bold(), error));
+        process::exit(2);
+    });
+    // Messages on stderr follow NO_COLOR and --no-color too
+    let colored = !no_color_env() && !args.no_color;
+
+    if args.version {
+        println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        return;
+    }
+
+    if args.gen_config {
+        let path = match args.config.or_else(Config::default_path) {
+            Some(path) => path,
+            None => {
+                eprintln!(
+                    "{}",
+                    paint(colored, Red.bold(), "Could not find the config directory.")
+                );
+                process::exit(1);
+            }
+        };
+
+        match Config::write_default(&path, args.force) {
+            Ok(()) => println!("Wrote the default config to {}", path.display()),
+            Err(error) => {
+                eprintln!("{}", paint(colored, Red.bold(), error.to_string()));
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut config = Config::new(args.config.clone()).unwrap_or_else(|error| {
+        eprintln!(
+            "{}",
+            paint(
+                colored,
+                Red.bold(),
+                "Error in config, falling back to default config."
+            )
+        );
+        match error {
+            ConfigError::Parse(_) => eprintln!(
+                "{} {}",
+                paint(colored, Red.bold(), error.to_string()),
+                paint(
+                    colored,
+                    Blue.bold(),
+                    "(line, column may differ from actual)"
+                )
+            ),
+            ConfigError::Io(_) | ConfigError::Syntax(_) => {
+                eprintln!("{}", paint(colored, Red.bold(), error.to_string()))
+            }
+        }
+        Config::fallback()
+    });
+
+    for warning in config.validate() {
+        eprintln!(
+            "{}",
+            paint(colored && config.colored_output, Yellow.bold(), warning)
+        );
+    }
+
+    if args.all || config.show_all {
+        config.show_everything();
+    }
+
+    if let Some(fields) = &args.fields {
+        config.select_fields(fields);
+    }
+
+    if args.list_modules {
+        config.print_modules();
+        return;
+    }
+
+    // Files get plain text unless asked otherwise
+    if !colored || args.output.is_some() && !args.ansi {
+        config.colored_output = false;
+    }
+    // The terminal's width has nothing to do with a file
+    if args.output.is_some() && config.max_width.is_none() {
+        config.max_width = Some(usize::MAX);
+    }
+
+    // Get only the system info the config asks for
+    let mut sys = System::new_with_specifics(config.refresh_kind());
+    config.sample_cpu_usage(&mut sys);
+
+    if let Some(interval) = args.watch {
+        // Traffic is shown per second once there is a previous refresh to compare with
+        config.traffic = Traffic::Rates(None);
+        let mut refreshed = Instant::now();
+        output(&config, &args, &sys);
+
+        // Nothing about the terminal is changed, so Ctrl-C can simply end the process
+        loop {
+            thread::sleep(interval);
+            // CPU usage is computed against the previous tick, no extra sample needed
+            sys.refresh_specifics(config.refresh_kind());
+            config.traffic = Traffic::Rates(Some(refreshed.elapsed()));
+            refreshed = Instant::now();
+            output(&config, &args, &sys);
+        }
+    }
+
+    output(&config, &args, &sys);
+}
+
+/// Prints the fetch, or writes it to the `--output` file, in the `--format` asked for.
+/// Text printed in watch mode replaces the previous fetch on screen.
+fn output(config: &Config, args: &Args, sys: &System) {
+    let info = rufetch::gather(config, sys);
+    let mut output = match args.format {
+        Format::Text => rufetch::render(&info, config),
+        Format::Oneline => rufetch::render_oneline(&info, config),
+        Format::Json => rufetch::render_json(&info),
+        Format::Kv => rufetch::render_kv(&info),
+    };
+
+    match &args.output {
+        Some(path) => {
+            if let Err(error) = fs::write(path, output) {
+                eprintln!(
+                    "{}",
+                    paint(
+                        !no_color_env() && !args.no_color,
+                        Red.bold(),
+                        format!("Could not write {}: {}", path.display(), error)
+                    )
+                );
+                process::exit(1);
+            }
+        }
+        None => {
+            let is_text = matches!(args.format, Format::Text | Format::Oneline);
+            if args.watch.is_some() && is_text {
+                // Clear the screen and move the cursor to the top left
+                output.insert_str(0, "\x1b[2J\x1b[H");
+            }
+            // A closed pipe, e.g. `rufetch | head`, isn't worth a panic
+            let _ = io::stdout().lock().write_all(output.as_bytes());
+        }
+    }
+}
+
+/// Whether `NO_COLOR` asks for plain text, see https://no-color.org: any non-empty
+/// value disables colors.
+fn no_color_env() -> bool {
+    env::var_os("NO_COLOR").map_or(false, |val| !val.is_empty())
+}
 
-    config.print(&sys);
+/// Paints a message for stderr with `style`, or returns it as is when `colored` is off.
+fn paint<S: AsRef<str>>(colored: bool, style: Style, text: S) -> String {
+    if colored {
+        style.paint(text.as_ref()).to_string()
+    } else {
+        text.as_ref().to_string()
+    }
 }
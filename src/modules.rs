@@ -0,0 +1,2740 @@
+use crate::conf::{usage_color, MEMORY_THRESHOLDS};
+use crate::datetime::DateTime;
+use crate::json::Value;
+use crate::types::{parse_color, Config, MemType, Time, Traffic};
+use ansi_term::{self, Color::*};
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, net::UdpSocket, ops::RangeInclusive, path::Path, process::Command};
+use sysinfo::{
+    get_current_pid, Component, ComponentExt, CpuExt, Disk, DiskExt, NetworkData, NetworkExt,
+    NetworksExt, ProcessExt, System, SystemExt,
+};
+
+/// A single piece of information shown by the fetch. Modules may be gathered
+/// on any thread, see [crate::gather].
+pub trait Module: Send {
+    /// Detects the module's information once, as the lines it prints and the raw values.
+    fn gather(&self, config: &Config, sys: &System) -> Output;
+}
+
+/// What a module found.
+#[derive(Default)]
+pub struct Output {
+    /// The lines of the text output, none if there is nothing to show.
+    pub lines: Vec<Line>,
+    /// The fields of the JSON and `key=value` output, with sizes in bytes and durations
+    /// in seconds so they don't depend on the configured units. Missing values are nulls.
+    pub values: Vec<(&'static str, Value)>,
+}
+
+impl Output {
+    /// Shorthand for an [Output] of `lines` and `values`.
+    pub fn new(lines: Vec<Line>, values: Vec<(&'static str, Value)>) -> Output {
+        Output { lines, values }
+    }
+}
+
+/// Names of all modules in their default order, as used by the `modules` config.
+pub const MODULE_NAMES: &[&str] = &[
+    "title",
+    "host",
+    "model",
+    "virt",
+    "os",
+    "de",
+    "wm",
+    "display_server",
+    "packages",
+    "shell",
+    "terminal",
+    "locale",
+    "uptime",
+    "boot_time",
+    "datetime",
+    "timezone",
+    "load_avg",
+    "processes",
+    "kernel",
+    "init",
+    "disks",
+    "cpu",
+    "cpu_temp",
+    "cpu_cache",
+    "gpu",
+    "resolution",
+    "memory",
+    "swap",
+    "local_ip",
+    "network",
+    "battery",
+    "temperature",
+    "colors",
+];
+
+/// Returns the module called `name`, see [MODULE_NAMES].
+pub fn module_by_name(name: &str) -> Option<Box<dyn Module>> {
+    let module: Box<dyn Module> = match name {
+        "title" => Box::new(Title),
+        "host" => Box::new(Host),
+        "model" => Box::new(Model),
+        "virt" => Box::new(Virt),
+        "os" => Box::new(Os),
+        "de" => Box::new(Desktop),
+        "wm" => Box::new(WindowManager),
+        "display_server" => Box::new(DisplayServer),
+        "packages" => Box::new(Packages),
+        "shell" => Box::new(Shell),
+        "terminal" => Box::new(Terminal),
+        "locale" => Box::new(Locale),
+        "uptime" => Box::new(Uptime),
+        "boot_time" => Box::new(BootTime),
+        "datetime" => Box::new(Clock),
+        "timezone" => Box::new(Timezone),
+        "load_avg" => Box::new(LoadAvg),
+        "processes" => Box::new(Processes),
+        "kernel" => Box::new(Kernel),
+        "init" => Box::new(Init),
+        "disks" => Box::new(Disks),
+        "cpu" => Box::new(Cpu),
+        "cpu_temp" => Box::new(CpuTemp),
+        "cpu_cache" => Box::new(CpuCache),
+        "gpu" => Box::new(Gpu),
+        "resolution" => Box::new(Resolution),
+        "memory" => Box::new(Memory),
+        "swap" => Box::new(Swap),
+        "local_ip" => Box::new(LocalIp),
+        "network" => Box::new(Network),
+        "battery" => Box::new(Battery),
+        "temperature" => Box::new(Temperature),
+        "colors" => Box::new(Colors),
+        _ => return None,
+    };
+    Some(module)
+}
+
+/// Returns the Nerd Font glyph shown before the labels of the module called `name`.
+pub fn default_icon(name: &str) -> Option<&'static str> {
+    let icon = match name {
+        "host" => "\u{f108}",
+        "model" => "\u{f109}",
+        "virt" => "\u{f233}",
+        "os" if cfg!(target_os = "windows") => "\u{f17a}",
+        "os" if cfg!(target_os = "macos") => "\u{f179}",
+        "os" => "\u{f17c}",
+        "de" => "\u{f2d0}",
+        "wm" => "\u{f2d2}",
+        "display_server" => "\u{f26c}",
+        "packages" => "\u{f187}",
+        "shell" => "\u{f120}",
+        "terminal" => "\u{f489}",
+        "locale" => "\u{f1ab}",
+        "uptime" => "\u{f017}",
+        "boot_time" => "\u{f011}",
+        "datetime" => "\u{f073}",
+        "timezone" => "\u{f0ac}",
+        "load_avg" => "\u{f080}",
+        "processes" => "\u{f0ae}",
+        "kernel" => "\u{f013}",
+        "init" => "\u{f085}",
+        "disks" => "\u{f0a0}",
+        "cpu" => "\u{f2db}",
+        "cpu_temp" => "\u{f2c9}",
+        "cpu_cache" => "\u{f1c0}",
+        "gpu" => "\u{f1b2}",
+        "resolution" => "\u{f03e}",
+        "memory" => "\u{f538}",
+        "swap" => "\u{f0ec}",
+        "local_ip" => "\u{f0ac}",
+        "network" => "\u{f1eb}",
+        "battery" => "\u{f240}",
+        _ => return None,
+    };
+    Some(icon)
+}
+
+/// A line of module output.
+#[derive(Clone)]
+pub enum Line {
+    /// A label such as `OS` and its value. The label is colored, followed by a colon
+    /// and aligned with the other labels when printed.
+    Field(String, String),
+    /// Printed as is, e.g. headers, tables and the color blocks.
+    Raw(String),
+}
+
+impl Line {
+    /// Shorthand for [Line::Field].
+    pub fn field<L: Into<String>, V: Into<String>>(label: L, value: V) -> Line {
+        Line::Field(label.into(), value.into())
+    }
+
+    /// Whether the line is a field without a value, ignoring whitespace and colors.
+    /// Empty raw lines are spacing, so they don't count.
+    pub fn is_blank(&self) -> bool {
+        match self {
+            Line::Field(_, value) => strip_ansi(value).trim().is_empty(),
+            Line::Raw(_) => false,
+        }
+    }
+}
+
+/// The `user@host` header and the separator below it.
+pub struct Title;
+
+impl Module for Title {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let separator = |length: usize| config.separator_char.to_string().repeat(length);
+        let user = Title::user();
+
+        let lines = match sys.host_name() {
+            Some(host_name) => {
+                let length = if config.separator_match_title {
+                    user.chars().count() + 1 + host_name.chars().count()
+                } else {
+                    config.separator_length
+                };
+                let mut lines = vec![Line::Raw(format!(
+                    "{}@{}",
+                    config.paint_label(&user),
+                    config.paint_label(host_name)
+                ))];
+                if length > 0 {
+                    lines.push(Line::Raw(separator(length)));
+                }
+                lines
+            }
+            None if config.separator_length > 0 => {
+                vec![Line::Raw(separator(config.separator_length))]
+            }
+            None => Vec::new(),
+        };
+        Output::new(lines, vec![("user", user.into())])
+    }
+}
+
+impl Title {
+    /// Gets the name of the current user.
+    ///
+    /// Reads `$USER` (`%USERNAME%` on windows) first and only spawns `whoami`/`id -un`
+    /// if it is unset or empty, falling back to `$LOGNAME` if that fails too.
+    pub fn user() -> String {
+        let user_var = if cfg!(target_os = "windows") {
+            "USERNAME"
+        } else {
+            "USER"
+        };
+        if let Ok(user) = env::var(user_var) {
+            if !user.is_empty() {
+                return user;
+            }
+        }
+
+        let user_out = if cfg!(target_os = "windows") || cfg!(target_os = "linux") {
+            // linux, windows
+            Command::new("whoami").output()
+        } else {
+            // darwin(mac)
+            Command::new("id").arg("-un").output()
+        };
+        let user = match user_out {
+            Ok(user_out) => String::from_utf8_lossy(&user_out.stdout)
+                .trim_end_matches(&['\r', '\n'][..])
+                .to_string(),
+            Err(_) => env::var("LOGNAME").unwrap_or_default(),
+        };
+        // whoami on windows returns DOMAIN\username
+        user.rsplit('\\').next().unwrap_or_default().to_string()
+    }
+}
+
+pub struct Host;
+
+impl Module for Host {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let host_name = sys.host_name();
+        let lines = host_name
+            .iter()
+            .map(|host_name| Line::field(config.label("host", "Host"), host_name))
+            .collect();
+        Output::new(lines, vec![("hostname", host_name.into())])
+    }
+}
+
+/// The machine's model or product name. Skips if nothing useful is found.
+pub struct Model;
+
+impl Module for Model {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let model = Model::detect();
+        let lines = model
+            .iter()
+            .map(|model| Line::field(config.label("model", "Model"), model))
+            .collect();
+        Output::new(lines, vec![("model", model.into())])
+    }
+}
+
+impl Model {
+    /// Gets the machine's model from DMI on Linux, `sysctl hw.model` on macOS
+    /// or `wmic computersystem` on Windows.
+    pub fn detect() -> Option<String> {
+        let model = if cfg!(target_os = "windows") {
+            let out = Command::new("wmic")
+                .args(["computersystem", "get", "model"])
+                .output()
+                .ok()?;
+            // First line is the "Model" header.
+            String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .nth(1)?
+                .trim()
+                .to_string()
+        } else if cfg!(target_os = "macos") {
+            let out = Command::new("sysctl")
+                .args(["-n", "hw.model"])
+                .output()
+                .ok()?;
+            String::from_utf8_lossy(&out.stdout).trim().to_string()
+        } else {
+            std::fs::read_to_string("/sys/devices/virtual/dmi/id/product_name")
+                .ok()?
+                .trim()
+                .to_string()
+        };
+
+        // Firmware placeholders carry no information.
+        let placeholders = [
+            "To be filled by O.E.M.",
+            "System Product Name",
+            "Default string",
+        ];
+        if model.is_empty() || placeholders.contains(&model.as_str()) {
+            return None;
+        }
+        Some(model)
+    }
+}
+
+/// The hypervisor of a virtual machine and the container runtime, skipped on bare metal.
+pub struct Virt;
+
+impl Module for Virt {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let hypervisor = Virt::hypervisor();
+        let container = Virt::container();
+
+        let mut lines = Vec::new();
+        if let Some(hypervisor) = &hypervisor {
+            lines.push(Line::field(
+                config.label("virt", "Virtualization"),
+                hypervisor,
+            ));
+        }
+        if let Some(container) = &container {
+            lines.push(Line::field(
+                config.label("container", "Container"),
+                container,
+            ));
+        }
+        Output::new(
+            lines,
+            vec![
+                ("virtualization", hypervisor.into()),
+                ("container", container.into()),
+            ],
+        )
+    }
+}
+
+impl Virt {
+    /// Asks `systemd-detect-virt` first, then looks for the marker files and cgroups
+    /// of Docker, Podman, LXC and Kubernetes. Linux only.
+    pub fn container() -> Option<String> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+        if let Some(container) = Virt::detect_virt("--container") {
+            return Some(container);
+        }
+
+        if Path::new("/.dockerenv").exists() {
+            return Some(String::from("docker"));
+        }
+        if Path::new("/run/.containerenv").exists() {
+            return Some(String::from("podman"));
+        }
+        let cgroup = std::fs::read_to_string("/proc/1/cgroup").ok()?;
+        ["docker", "lxc", "kubepods"]
+            .iter()
+            .find(|runtime| cgroup.contains(*runtime))
+            .map(|runtime| match *runtime {
+                "kubepods" => String::from("kubernetes"),
+                runtime => runtime.to_string(),
+            })
+    }
+
+    /// Asks `systemd-detect-virt` first, then falls back to the CPU's hypervisor flag
+    /// and names the hypervisor by its DMI vendor. Linux only.
+    pub fn hypervisor() -> Option<String> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+        if let Some(hypervisor) = Virt::detect_virt("--vm") {
+            return Some(hypervisor);
+        }
+
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+        let flagged = cpuinfo
+            .lines()
+            .filter(|line| line.starts_with("flags"))
+            .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"));
+        if !flagged {
+            return None;
+        }
+
+        let vendor = std::fs::read_to_string("/sys/class/dmi/id/sys_vendor").unwrap_or_default();
+        let hypervisor = match vendor.trim() {
+            "QEMU" => "qemu",
+            "VMware, Inc." => "vmware",
+            "innotek GmbH" | "Oracle Corporation" => "oracle",
+            "Microsoft Corporation" => "microsoft",
+            "Xen" => "xen",
+            _ => "unknown",
+        };
+        Some(hypervisor.to_string())
+    }
+
+    /// Runs `systemd-detect-virt` with `kind`, `None` when it finds nothing or isn't installed.
+    fn detect_virt(kind: &str) -> Option<String> {
+        let out = Command::new("systemd-detect-virt")
+            .arg(kind)
+            .output()
+            .ok()?;
+        // It prints "none" and fails when there is no virtualization.
+        let name = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        if !out.status.success() || name.is_empty() || name == "none" {
+            return None;
+        }
+        Some(name)
+    }
+}
+
+pub struct Os;
+
+impl Module for Os {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let os = Os::detect(sys);
+        let wsl = Os::wsl();
+        let lines = os
+            .iter()
+            .map(|os| match wsl {
+                Some(wsl) => format!("{} ({})", os, wsl),
+                None => os.clone(),
+            })
+            .map(|os| Line::field(config.label("os", "OS"), os))
+            .collect();
+        Output::new(lines, vec![("os", os.into()), ("wsl", wsl.into())])
+    }
+}
+
+impl Os {
+    /// Gets the OS name, preferring `PRETTY_NAME` from `/etc/os-release` on Linux
+    /// (e.g. `Arch Linux`), then sysinfo's long OS version, then its name and version
+    /// (e.g. `Windows 11`). Either of the last two is used alone if the other is missing.
+    pub fn detect(sys: &System) -> Option<String> {
+        if cfg!(target_os = "linux") {
+            if let Some(pretty_name) = Os::release_field("PRETTY_NAME") {
+                return Some(pretty_name);
+            }
+        }
+
+        if let Some(long_version) = sys.long_os_version().filter(|os| !os.trim().is_empty()) {
+            return Some(long_version);
+        }
+
+        let parts: Vec<String> = [sys.name(), sys.os_version()]
+            .iter()
+            .flatten()
+            .map(|part| part.trim().to_string())
+            .filter(|part| !part.is_empty())
+            .collect();
+        Some(parts.join(" ")).filter(|os| !os.is_empty())
+    }
+
+    /// Tells whether Linux runs under WSL, from `WSL_DISTRO_NAME` or `microsoft` in
+    /// `/proc/version`. WSL2 runs a real kernel whose version ends in `microsoft-standard`.
+    pub fn wsl() -> Option<&'static str> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+
+        let version = std::fs::read_to_string("/proc/version")
+            .unwrap_or_default()
+            .to_lowercase();
+        if version.contains("microsoft-standard") || env::var_os("WSL_INTEROP").is_some() {
+            Some("WSL2")
+        } else if version.contains("microsoft") || env::var_os("WSL_DISTRO_NAME").is_some() {
+            Some("WSL")
+        } else {
+            None
+        }
+    }
+
+    /// Reads `key` from `/etc/os-release`, without the quotes around its value.
+    pub fn release_field(key: &str) -> Option<String> {
+        let release = std::fs::read_to_string("/etc/os-release").ok()?;
+        release
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(name, _)| name.trim() == key)
+            .map(|(_, value)| value.trim().trim_matches('"').to_string())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// --------------- Linux only --------------------
+///
+/// The current desktop enviroment. The information might not be 100% accurate.
+/// Skips if nothing useful is found.
+pub struct Desktop;
+
+impl Module for Desktop {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let de = Desktop::detect();
+        let version = de.as_deref().and_then(Desktop::version);
+        let lines = de
+            .iter()
+            .map(|de| {
+                let de = match &version {
+                    Some(version) => format!("{} {}", de, version),
+                    None => de.clone(),
+                };
+                Line::field(config.label("de", "DE"), de)
+            })
+            .collect();
+        Output::new(
+            lines,
+            vec![("de", de.into()), ("de_version", version.into())],
+        )
+    }
+}
+
+impl Desktop {
+    /// Gets the current desktop enviroment.
+    pub fn detect() -> Option<String> {
+        if cfg!(target_os = "linux") {
+            // linux
+            // XDG_CURRENT_DESKTOP is preferred since DESKTOP_SESSION is often just a session file name.
+            env::var("XDG_CURRENT_DESKTOP")
+                .ok()
+                .and_then(|desktops| Desktop::current(&desktops))
+                .or_else(|| {
+                    env::var("DESKTOP_SESSION")
+                        .ok()
+                        .filter(|val| !val.is_empty())
+                })
+        } else {
+            None
+        }
+    }
+
+    /// Picks the desktop out of a colon-separated `XDG_CURRENT_DESKTOP`, e.g. `GNOME` out of
+    /// `ubuntu:GNOME`. Distributions put their own name before the desktop they build on.
+    fn current(desktops: &str) -> Option<String> {
+        desktops
+            .rsplit(':')
+            .map(str::trim)
+            .find(|desktop| !desktop.is_empty())
+            .map(String::from)
+    }
+
+    /// Asks GNOME, KDE Plasma or Xfce for its version, picked by the name `detect` found.
+    /// Returns `None` for other desktops or if the probe fails.
+    pub fn version(de: &str) -> Option<String> {
+        let de = de.to_lowercase();
+        let program = if de.contains("gnome") {
+            "gnome-shell"
+        } else if de.contains("kde") || de.contains("plasma") {
+            "plasmashell"
+        } else if de.contains("xfce") {
+            "xfce4-session"
+        } else {
+            return None;
+        };
+        // They all print e.g. `GNOME Shell 45.2` like a shell's `--version`.
+        Shell::version(program)
+    }
+}
+
+/// --------------- Linux only --------------------
+///
+/// The window manager or Wayland compositor, independent of the desktop enviroment.
+/// Skips if nothing useful is found.
+pub struct WindowManager;
+
+impl Module for WindowManager {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let wm = WindowManager::detect();
+        let lines = wm
+            .iter()
+            .map(|wm| Line::field(config.label("wm", "WM"), wm))
+            .collect();
+        Output::new(lines, vec![("wm", wm.into())])
+    }
+}
+
+impl WindowManager {
+    /// Gets the window manager, from the sockets Wayland compositors advertise,
+    /// then `wmctrl -m`, then the EWMH `_NET_WM_NAME` of the window `xprop` reports
+    /// as `_NET_SUPPORTING_WM_CHECK`.
+    pub fn detect() -> Option<String> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+
+        let compositors = [
+            ("SWAYSOCK", "sway"),
+            ("HYPRLAND_INSTANCE_SIGNATURE", "Hyprland"),
+            ("NIRI_SOCKET", "niri"),
+        ];
+        if let Some((_, name)) = compositors
+            .iter()
+            .find(|(var, _)| env::var_os(var).is_some())
+        {
+            return Some(name.to_string());
+        }
+
+        let run = |program: &str, args: &[&str]| -> Option<String> {
+            let out = Command::new(program).args(args).output().ok()?;
+            if out.status.success() {
+                Some(String::from_utf8_lossy(&out.stdout).to_string())
+            } else {
+                None
+            }
+        };
+
+        // e.g. "Name: i3"
+        if let Some(stdout) = run("wmctrl", &["-m"]) {
+            let name = stdout
+                .lines()
+                .find_map(|line| line.strip_prefix("Name:"))
+                .map(|name| name.trim().to_string());
+            if let Some(name) = name.filter(|name| !name.is_empty() && name != "N/A") {
+                return Some(name);
+            }
+        }
+
+        // e.g. "_NET_SUPPORTING_WM_CHECK(WINDOW): window id # 0x600001"
+        let check = run("xprop", &["-root", "_NET_SUPPORTING_WM_CHECK"])?;
+        let id = check.split('#').nth(1)?.trim().to_string();
+        // e.g. "_NET_WM_NAME(UTF8_STRING) = "i3""
+        let name = run("xprop", &["-id", &id, "_NET_WM_NAME"])?;
+        let name = name.split('=').nth(1)?.trim().trim_matches('"').to_string();
+        Some(name).filter(|name| !name.is_empty())
+    }
+}
+
+/// --------------- Linux only --------------------
+///
+/// Whether the session runs on X11 or Wayland. Skips on a tty.
+pub struct DisplayServer;
+
+impl Module for DisplayServer {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let server = DisplayServer::detect();
+        let lines = server
+            .iter()
+            .map(|server| Line::field(config.label("display_server", "Display Server"), server))
+            .collect();
+        Output::new(lines, vec![("display_server", server.into())])
+    }
+}
+
+impl DisplayServer {
+    /// Gets the display server from `XDG_SESSION_TYPE`, then `WAYLAND_DISPLAY` and `DISPLAY`.
+    pub fn detect() -> Option<String> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+
+        let server = match env::var("XDG_SESSION_TYPE").as_deref() {
+            Ok("wayland") => "Wayland",
+            Ok("x11") => "X11",
+            _ if env::var_os("WAYLAND_DISPLAY").is_some() => "Wayland",
+            _ if env::var_os("DISPLAY").is_some() => "X11",
+            _ => return None,
+        };
+        Some(server.to_string())
+    }
+}
+
+pub struct Packages;
+
+impl Module for Packages {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let packages = Packages::detect();
+        let counts = packages
+            .iter()
+            .map(|&(count, manager)| (manager, count.into()))
+            .collect();
+        let values = vec![("packages", Value::object(counts))];
+        if packages.is_empty() {
+            return Output::new(Vec::new(), values);
+        }
+
+        let counts: Vec<String> = packages
+            .iter()
+            .map(|(count, manager)| format!("{} ({})", count, manager))
+            .collect();
+        let lines = vec![Line::field(
+            config.label("packages", "Packages"),
+            counts.join(", "),
+        )];
+        Output::new(lines, values)
+    }
+}
+
+impl Packages {
+    /// Counts the installed packages of every package manager found on the system.
+    /// Managers that are missing or have nothing installed are left out.
+    pub fn detect() -> Vec<(usize, &'static str)> {
+        let managers: &[(&'static str, &[&str])] = if cfg!(target_os = "macos") {
+            &[("brew", &["list", "-1"])]
+        } else if cfg!(target_os = "windows") {
+            &[]
+        } else {
+            &[
+                ("pacman", &["-Qq"]),
+                ("dpkg", &["--get-selections"]),
+                ("rpm", &["-qa"]),
+                ("flatpak", &["list"]),
+                ("snap", &["list"]),
+            ]
+        };
+
+        managers
+            .iter()
+            .filter_map(|(manager, args)| {
+                let out = Command::new(manager).args(*args).output().ok()?;
+                if !out.status.success() {
+                    return None;
+                }
+
+                let stdout = String::from_utf8_lossy(&out.stdout);
+                let lines = stdout.lines().filter(|line| !line.trim().is_empty());
+                let count = match *manager {
+                    // Removed packages are listed as "deinstall".
+                    "dpkg" => lines
+                        .filter(|line| line.split_whitespace().nth(1) == Some("install"))
+                        .count(),
+                    // Skip the header.
+                    "snap" => lines.count().saturating_sub(1),
+                    _ => lines.count(),
+                };
+
+                Some((count, *manager)).filter(|(count, _)| *count > 0)
+            })
+            .collect()
+    }
+}
+
+pub struct Shell;
+
+impl Module for Shell {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let (name, path) = match Shell::detect() {
+            Some(shell) => shell,
+            None => {
+                return Output::new(
+                    Vec::new(),
+                    vec![("shell", Value::Null), ("shell_version", Value::Null)],
+                )
+            }
+        };
+        let version = Shell::version(&path);
+        let line = match &version {
+            Some(version) => Line::field(
+                config.label("shell", "Shell"),
+                format!("{} {}", name, version),
+            ),
+            None => Line::field(config.label("shell", "Shell"), &name),
+        };
+        Output::new(
+            vec![line],
+            vec![("shell", name.into()), ("shell_version", version.into())],
+        )
+    }
+}
+
+impl Shell {
+    /// Gets the name of the current shell along with the path it was found at.
+    ///
+    /// On Windows, PowerShell is detected through `PSModulePath` (which sessions extend with
+    /// the user's module directory), otherwise `ComSpec` is used.
+    pub fn detect() -> Option<(String, String)> {
+        let path = if cfg!(target_os = "windows") {
+            match env::var("PSModulePath") {
+                Ok(modules) if modules.contains("PowerShell\\7") => String::from("pwsh"),
+                Ok(modules) if modules.contains("Documents") => String::from("powershell"),
+                _ => env::var("ComSpec").ok()?,
+            }
+        } else {
+            env::var("SHELL").ok()?
+        };
+
+        let name = Path::new(&path).file_stem()?.to_string_lossy().to_string();
+        if name.is_empty() {
+            return None;
+        }
+        Some((name, path))
+    }
+
+    /// Runs `<shell> --version` and extracts the version number from the first line.
+    /// Returns `None` if the probe fails or no version is found.
+    pub fn version(path: &str) -> Option<String> {
+        if cfg!(target_os = "windows") {
+            return None;
+        }
+
+        let out = Command::new(path).arg("--version").output().ok()?;
+        if !out.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        let version = stdout
+            .lines()
+            .next()?
+            .split_whitespace()
+            .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?
+            .split(|c: char| !c.is_ascii_digit() && c != '.')
+            .next()?
+            .trim_end_matches('.')
+            .to_string();
+
+        Some(version)
+    }
+}
+
+pub struct Terminal;
+
+impl Module for Terminal {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let terminal = Terminal::detect(sys);
+        let lines = terminal
+            .iter()
+            .map(|terminal| Line::field(config.label("terminal", "Terminal"), terminal))
+            .collect();
+        Output::new(lines, vec![("terminal", terminal.into())])
+    }
+}
+
+impl Terminal {
+    /// Gets the terminal emulator the program is running in.
+    ///
+    /// Checks `WT_SESSION` (Windows Terminal), `TERM_PROGRAM` and a non-generic `TERM` first,
+    /// then walks up the parent processes skipping shells until something else is found.
+    pub fn detect(sys: &System) -> Option<String> {
+        if cfg!(target_os = "windows") && env::var_os("WT_SESSION").is_some() {
+            return Some(String::from("Windows Terminal"));
+        }
+
+        if let Ok(program) = env::var("TERM_PROGRAM") {
+            if !program.is_empty() {
+                return Some(program);
+            }
+        }
+
+        if let Ok(term) = env::var("TERM") {
+            // e.g. "xterm-kitty" only tells us the terminal through its suffix.
+            let term = match term.strip_prefix("xterm-") {
+                Some(suffix) if !suffix.contains("color") => suffix.to_string(),
+                _ => term,
+            };
+            let generic = ["xterm", "screen", "tmux", "vt", "dumb", "ansi"];
+            if !term.is_empty() && !generic.iter().any(|g| term.starts_with(g)) {
+                return Some(term);
+            }
+        }
+
+        let shells = [
+            "sh",
+            "bash",
+            "zsh",
+            "fish",
+            "dash",
+            "ksh",
+            "tcsh",
+            "csh",
+            "nu",
+            "elvish",
+            "xonsh",
+            "sudo",
+            "su",
+            "doas",
+            "login",
+            "cmd",
+            "powershell",
+            "pwsh",
+        ];
+        let mut pid = get_current_pid().ok()?;
+        loop {
+            let parent = sys.process(sys.process(pid)?.parent()?)?;
+            let name = Path::new(parent.name()).file_stem()?.to_string_lossy();
+            if !shells.contains(&name.as_ref()) {
+                return Some(name.to_string());
+            }
+            pid = parent.pid();
+        }
+    }
+}
+
+/// The user's locale, e.g. `en_US.UTF-8`.
+pub struct Locale;
+
+impl Module for Locale {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let locale = Locale::detect();
+        let lines = locale
+            .iter()
+            .map(|locale| Line::field(config.label("locale", "Locale"), locale))
+            .collect();
+        Output::new(lines, vec![("locale", locale.into())])
+    }
+}
+
+impl Locale {
+    /// Gets the locale from `$LC_ALL`, which overrides every category, then `$LANG`.
+    #[cfg(not(windows))]
+    pub fn detect() -> Option<String> {
+        ["LC_ALL", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|locale| !locale.is_empty())
+    }
+
+    /// Gets the user's default locale name, e.g. `en-US`.
+    #[cfg(windows)]
+    pub fn detect() -> Option<String> {
+        use winapi::um::winnls::GetUserDefaultLocaleName;
+        use winapi::um::winnt::LOCALE_NAME_MAX_LENGTH;
+
+        let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+        // The length includes the trailing NUL, 0 means it failed.
+        let len = unsafe { GetUserDefaultLocaleName(name.as_mut_ptr(), name.len() as i32) };
+        if len <= 1 {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&name[..len as usize - 1]))
+    }
+}
+
+pub struct Uptime;
+
+impl Module for Uptime {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let precision = config.precision;
+        let uptime = match config.uptime_type {
+            Time::Second => format!("{} sec(s)", sys.uptime()),
+            Time::Minute => format!("{:.*} min(s)", precision, sys.uptime() as f64 / 60.0),
+            Time::Hour => format!("{:.*} hour(s)", precision, sys.uptime() as f64 / 3600.0),
+            Time::Day => format!("{:.*} day(s)", precision, sys.uptime() as f64 / 86400.0),
+            Time::Auto => format_duration(sys.uptime()),
+            Time::Human => format_human_duration(sys.uptime()),
+            Time::Both => match DateTime::local(sys.boot_time() as i64) {
+                Some(boot) => format!(
+                    "{} (since {})",
+                    format_duration(sys.uptime()),
+                    boot.format(&config.datetime_format)
+                ),
+                None => format_duration(sys.uptime()),
+            },
+        };
+
+        Output::new(
+            vec![Line::field(config.label("uptime", "Uptime"), uptime)],
+            vec![("uptime_seconds", sys.uptime().into())],
+        )
+    }
+}
+
+/// The local date and time the system booted at.
+pub struct BootTime;
+
+impl Module for BootTime {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let lines = DateTime::local(sys.boot_time() as i64)
+            .map(|boot| {
+                Line::field(
+                    config.label("boot_time", "Booted"),
+                    boot.format(&config.datetime_format),
+                )
+            })
+            .into_iter()
+            .collect();
+        Output::new(lines, vec![("boot_time", sys.boot_time().into())])
+    }
+}
+
+/// The current local date and time.
+pub struct Clock;
+
+impl Module for Clock {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let lines = DateTime::now()
+            .map(|now| {
+                Line::field(
+                    config.label("datetime", "Time"),
+                    now.format(&config.datetime_format),
+                )
+            })
+            .into_iter()
+            .collect();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH);
+        Output::new(
+            lines,
+            vec![("datetime", now.ok().map(|now| now.as_secs()).into())],
+        )
+    }
+}
+
+/// The local timezone's name, e.g. `CET`.
+pub struct Timezone;
+
+impl Module for Timezone {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let zone = Timezone::detect();
+        let lines = zone
+            .iter()
+            .map(|zone| Line::field(config.label("timezone", "Timezone"), zone))
+            .collect();
+        Output::new(lines, vec![("timezone", zone.into())])
+    }
+}
+
+impl Timezone {
+    /// Gets the zone's name from the local time, falling back to `$TZ`.
+    pub fn detect() -> Option<String> {
+        if let Some(now) = DateTime::now().filter(|now| !now.zone.is_empty()) {
+            return Some(now.zone);
+        }
+        // A leading colon only tells the C library to read the zone from a file.
+        env::var("TZ")
+            .ok()
+            .map(|zone| zone.trim_start_matches(':').to_string())
+            .filter(|zone| !zone.is_empty())
+    }
+}
+
+/// The 1, 5 and 15 minute load averages. Skips on Windows, which has none.
+pub struct LoadAvg;
+
+impl Module for LoadAvg {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        if cfg!(target_os = "windows") {
+            return Output::default();
+        }
+
+        let load = sys.load_average();
+        Output::new(
+            vec![Line::field(
+                config.label("load_avg", "Load"),
+                format!("{:.2} {:.2} {:.2}", load.one, load.five, load.fifteen),
+            )],
+            vec![(
+                "load_average",
+                vec![load.one, load.five, load.fifteen].into(),
+            )],
+        )
+    }
+}
+
+/// The number of running processes.
+pub struct Processes;
+
+impl Module for Processes {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let processes = sys.processes().len();
+        Output::new(
+            vec![Line::field(
+                config.label("processes", "Processes"),
+                processes.to_string(),
+            )],
+            vec![("processes", processes.into())],
+        )
+    }
+}
+
+pub struct Kernel;
+
+impl Module for Kernel {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let kernel_version = sys.kernel_version();
+        let lines = kernel_version
+            .iter()
+            .map(|kernel_ver| {
+                if config.show_arch {
+                    Line::field(
+                        config.label("kernel", "Kernel Version"),
+                        format!("{} ({})", kernel_ver, env::consts::ARCH),
+                    )
+                } else {
+                    Line::field(config.label("kernel", "Kernel Version"), kernel_ver)
+                }
+            })
+            .collect();
+
+        let mut values = vec![("kernel_version", kernel_version.into())];
+        if config.show_arch {
+            values.push(("arch", env::consts::ARCH.into()));
+        }
+        Output::new(lines, values)
+    }
+}
+
+/// --------------- Linux only --------------------
+///
+/// The init system, from the name of PID 1.
+pub struct Init;
+
+impl Module for Init {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let init = Init::detect();
+        let lines = init
+            .iter()
+            .map(|init| Line::field(config.label("init", "Init"), init))
+            .collect();
+        Output::new(lines, vec![("init", init.into())])
+    }
+}
+
+impl Init {
+    /// Reads `/proc/1/comm`. A plain `init` is told apart by the files OpenRC and runit
+    /// leave behind, and is SysVinit otherwise.
+    pub fn detect() -> Option<String> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+
+        let comm = std::fs::read_to_string("/proc/1/comm").ok()?;
+        Init::from_comm(comm.trim()).map(String::from)
+    }
+
+    /// Maps the name of PID 1 to its init system. Anything else, such as the entry point
+    /// of a container, is no init system and gives `None`.
+    fn from_comm(comm: &str) -> Option<&'static str> {
+        let init = match comm {
+            "systemd" => "systemd",
+            "runit" | "runit-init" => "runit",
+            "openrc-init" => "openrc",
+            "s6-svscan" => "s6",
+            "dinit" => "dinit",
+            "shepherd" => "shepherd",
+            "init" if Path::new("/run/openrc").exists() => "openrc",
+            "init" if Path::new("/run/runit").exists() => "runit",
+            "init" => "sysvinit",
+            _ => return None,
+        };
+        Some(init)
+    }
+}
+
+pub struct Disks;
+
+impl Module for Disks {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let disks = Disks::shown(config, sys);
+
+        let mut lines: Vec<Line> = disks
+            .iter()
+            .map(|disk| {
+                let mut line = config.paint(Yellow.bold(), disk.name().to_string_lossy());
+                // A long mount point in the label would widen the column of every label
+                if config.show_disk_mount {
+                    line.push_str(&format!(" ({})", disk.mount_point().display()));
+                }
+                if config.show_disk_fs {
+                    line.push_str(&format!(" {}", String::from_utf8_lossy(disk.file_system())));
+                }
+                line.push(' ');
+                line.push_str(&Disks::usage(config, Disks::used(disk), disk.total_space()));
+                Line::field(config.label("disks", "Disk"), line)
+            })
+            .collect();
+
+        if config.show_disk_total && !disks.is_empty() {
+            let used = disks.iter().map(|disk| Disks::used(disk)).sum();
+            let total = disks.iter().map(|disk| disk.total_space()).sum();
+            lines.push(Line::field(
+                config.label("disk_total", "Disk Total"),
+                Disks::usage(config, used, total),
+            ));
+        }
+
+        let values = disks
+            .iter()
+            .map(|disk| {
+                let mut fields = vec![
+                    ("name", disk.name().to_string_lossy().as_ref().into()),
+                    ("used", Disks::used(disk).into()),
+                    ("total", disk.total_space().into()),
+                ];
+                if config.show_disk_mount {
+                    let mount = disk.mount_point().to_string_lossy();
+                    fields.push(("mount_point", mount.as_ref().into()));
+                }
+                if config.show_disk_fs {
+                    let fs = String::from_utf8_lossy(disk.file_system());
+                    fields.push(("file_system", fs.as_ref().into()));
+                }
+                Value::object(fields)
+            })
+            .collect();
+        Output::new(lines, vec![("disks", Value::Array(values))])
+    }
+}
+
+impl Disks {
+    /// Returns the used space of `disk`. This counts blocks reserved for root as used,
+    /// but never exceeds the total.
+    pub fn used(disk: &Disk) -> u64 {
+        disk.total_space().saturating_sub(disk.available_space())
+    }
+
+    /// Formats `(used / total)` in the disk unit, colored by the disk thresholds and
+    /// followed by a usage bar if enabled.
+    fn usage(config: &Config, used: u64, total: u64) -> String {
+        let unit = config.disk_type.as_ref().unwrap_or(&config.memory_type);
+        let thresholds = (config.disk_warn_percent, config.disk_crit_percent);
+
+        let sizes = format!(
+            "({} / {})",
+            humanize(used, unit, config.precision),
+            humanize(total, unit, config.precision)
+        );
+        let mut usage = match percent(used, total) {
+            Some(percent) => config.paint(usage_color(percent, thresholds).normal(), sizes),
+            None => sizes,
+        };
+        if config.usage_bars {
+            if let Some(bar) = config.render_bar(used, total, config.bar_width, thresholds) {
+                usage.push_str(&format!(" {}", bar));
+            }
+        }
+
+        usage
+    }
+
+    /// Returns the disks left after `disk_include`, `disk_exclude` and `hide_removable`,
+    /// or the ones mounted at the `disks` mount points in their order if that is set.
+    /// Mount points match a prefix by whole components, so `/snap` doesn't match `/snapshots`.
+    pub fn shown<'a>(config: &Config, sys: &'a System) -> Vec<&'a Disk> {
+        if let Some(mounts) = &config.disks {
+            return mounts
+                .iter()
+                .filter_map(|mount| {
+                    sys.disks()
+                        .iter()
+                        .find(|disk| disk.mount_point() == Path::new(mount))
+                })
+                .collect();
+        }
+
+        let matches = |disk: &Disk, prefixes: &[String]| {
+            prefixes
+                .iter()
+                .any(|prefix| disk.mount_point().starts_with(prefix))
+        };
+
+        sys.disks()
+            .iter()
+            .filter(|disk| config.disk_include.is_empty() || matches(disk, &config.disk_include))
+            .filter(|disk| !matches(disk, &config.disk_exclude))
+            .filter(|disk| !(config.hide_removable && disk.is_removable()))
+            .collect()
+    }
+}
+
+pub struct Cpu;
+
+impl Module for Cpu {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let mut cpu_str = sys.global_cpu_info().brand().to_string();
+        let mut values = vec![("cpu_brand", cpu_str.as_str().into())];
+        if config.show_cpu_codename {
+            let codename = Cpu::codename(sys);
+            if let Some(codename) = codename {
+                cpu_str.push_str(&format!(" ({})", codename));
+            }
+            values.push(("cpu_codename", codename.into()));
+        }
+
+        let per_core = config.show_cores && config.show_per_core_usage;
+        if config.show_cores {
+            let threads = sys.cpus().len();
+            let cores = sys.physical_core_count();
+            values.push(("cores", threads.into()));
+            if config.show_threads {
+                if let Some(cores) = cores {
+                    values.push(("physical_cores", cores.into()));
+                }
+            }
+            if !per_core {
+                match cores {
+                    Some(cores) if config.show_threads && cores != threads => {
+                        cpu_str.push_str(&format!(" ({}C/{}T)", cores, threads))
+                    }
+                    _ => cpu_str.push_str(&format!(" ({})", threads)),
+                }
+            }
+        }
+
+        let mut shows_freq = false;
+        if config.show_cpu_freq {
+            // The global CPU doesn't always carry a frequency, the first core does.
+            let freq = match sys.global_cpu_info().frequency() {
+                0 => sys.cpus().first().map_or(0, |cpu| cpu.frequency()),
+                freq => freq,
+            };
+            if freq >= 1000 {
+                cpu_str.push_str(&format!(" @ {:.2} GHz", freq as f64 / 1000.0));
+            } else if freq > 0 {
+                cpu_str.push_str(&format!(" @ {} MHz", freq));
+            }
+            shows_freq = freq > 0;
+            values.push(("cpu_frequency_mhz", freq.into()));
+        }
+
+        if config.show_cpu_usage {
+            let usage = sys.global_cpu_info().cpu_usage();
+            // e.g. `@ 2.10 GHz (5%)` rather than a second `@`
+            if shows_freq {
+                cpu_str.push_str(&format!(" ({:.0}%)", usage));
+            } else {
+                cpu_str.push_str(&format!(" @ {:.0}%", usage));
+            }
+            values.push(("cpu_usage", usage.into()));
+        }
+
+        let mut lines = vec![Line::field(config.label("cpu", "CPU"), cpu_str)];
+        if per_core {
+            lines.extend(Cpu::core_usage(config, sys).into_iter().map(Line::Raw));
+            let usage: Vec<f32> = sys.cpus().iter().map(|cpu| cpu.cpu_usage()).collect();
+            values.push(("core_usage", usage.into()));
+        }
+
+        Output::new(lines, values)
+    }
+}
+
+/// The vendor, family, models and steppings a [CPU_CODENAMES] entry matches, and its codename.
+type CodenameEntry = (
+    &'static str,
+    u32,
+    RangeInclusive<u32>,
+    RangeInclusive<u32>,
+    &'static str,
+);
+
+/// Microarchitectures by vendor, family, models and steppings, the first match wins.
+/// Families and models are the decimal ones `/proc/cpuinfo` reports.
+const CPU_CODENAMES: &[CodenameEntry] = &[
+    ("AuthenticAMD", 23, 8..=8, 0..=255, "Zen+"),
+    ("AuthenticAMD", 23, 24..=24, 0..=255, "Zen+"),
+    ("AuthenticAMD", 23, 0..=47, 0..=255, "Zen"),
+    ("AuthenticAMD", 23, 48..=255, 0..=255, "Zen 2"),
+    ("AuthenticAMD", 25, 16..=31, 0..=255, "Zen 4"),
+    ("AuthenticAMD", 25, 64..=79, 0..=255, "Zen 3+"),
+    ("AuthenticAMD", 25, 96..=127, 0..=255, "Zen 4"),
+    ("AuthenticAMD", 25, 160..=175, 0..=255, "Zen 4"),
+    ("AuthenticAMD", 25, 0..=255, 0..=255, "Zen 3"),
+    ("AuthenticAMD", 26, 0..=255, 0..=255, "Zen 5"),
+    ("GenuineIntel", 6, 42..=42, 0..=255, "Sandy Bridge"),
+    ("GenuineIntel", 6, 45..=45, 0..=255, "Sandy Bridge-E"),
+    ("GenuineIntel", 6, 58..=58, 0..=255, "Ivy Bridge"),
+    ("GenuineIntel", 6, 62..=62, 0..=255, "Ivy Bridge-E"),
+    ("GenuineIntel", 6, 60..=60, 0..=255, "Haswell"),
+    ("GenuineIntel", 6, 63..=63, 0..=255, "Haswell-E"),
+    ("GenuineIntel", 6, 69..=70, 0..=255, "Haswell"),
+    ("GenuineIntel", 6, 61..=61, 0..=255, "Broadwell"),
+    ("GenuineIntel", 6, 71..=71, 0..=255, "Broadwell"),
+    ("GenuineIntel", 6, 79..=79, 0..=255, "Broadwell-E"),
+    ("GenuineIntel", 6, 78..=78, 0..=255, "Skylake"),
+    ("GenuineIntel", 6, 94..=94, 0..=255, "Skylake"),
+    ("GenuineIntel", 6, 85..=85, 0..=4, "Skylake-SP"),
+    ("GenuineIntel", 6, 85..=85, 5..=255, "Cascade Lake"),
+    ("GenuineIntel", 6, 142..=142, 0..=9, "Kaby Lake"),
+    ("GenuineIntel", 6, 142..=142, 10..=10, "Kaby Lake R"),
+    ("GenuineIntel", 6, 142..=142, 11..=11, "Whiskey Lake"),
+    ("GenuineIntel", 6, 142..=142, 12..=255, "Comet Lake"),
+    ("GenuineIntel", 6, 158..=158, 0..=9, "Kaby Lake"),
+    ("GenuineIntel", 6, 158..=158, 10..=255, "Coffee Lake"),
+    ("GenuineIntel", 6, 165..=166, 0..=255, "Comet Lake"),
+    ("GenuineIntel", 6, 125..=126, 0..=255, "Ice Lake"),
+    ("GenuineIntel", 6, 106..=108, 0..=255, "Ice Lake-SP"),
+    ("GenuineIntel", 6, 140..=141, 0..=255, "Tiger Lake"),
+    ("GenuineIntel", 6, 167..=167, 0..=255, "Rocket Lake"),
+    ("GenuineIntel", 6, 151..=151, 0..=255, "Alder Lake"),
+    ("GenuineIntel", 6, 154..=154, 0..=255, "Alder Lake"),
+    ("GenuineIntel", 6, 183..=183, 0..=255, "Raptor Lake"),
+    ("GenuineIntel", 6, 186..=186, 0..=255, "Raptor Lake"),
+    ("GenuineIntel", 6, 191..=191, 0..=255, "Raptor Lake"),
+    ("GenuineIntel", 6, 143..=143, 0..=255, "Sapphire Rapids"),
+    ("GenuineIntel", 6, 207..=207, 0..=255, "Emerald Rapids"),
+    ("GenuineIntel", 6, 170..=170, 0..=255, "Meteor Lake"),
+    ("GenuineIntel", 6, 189..=189, 0..=255, "Lunar Lake"),
+    ("GenuineIntel", 6, 197..=198, 0..=255, "Arrow Lake"),
+];
+
+impl Cpu {
+    /// Looks the CPU's microarchitecture up in [CPU_CODENAMES], e.g. `Zen 3`.
+    /// Returns `None` for CPUs the table doesn't know.
+    pub fn codename(sys: &System) -> Option<&'static str> {
+        let vendor = sys.global_cpu_info().vendor_id();
+        let (family, model, stepping) = Cpu::signature()?;
+
+        CPU_CODENAMES
+            .iter()
+            .find(|(known, known_family, models, steppings, _)| {
+                *known == vendor
+                    && *known_family == family
+                    && models.contains(&model)
+                    && steppings.contains(&stepping)
+            })
+            .map(|&(_, _, _, _, codename)| codename)
+    }
+
+    /// Reads the family, model and stepping of the first CPU from `/proc/cpuinfo`.
+    #[cfg(target_os = "linux")]
+    fn signature() -> Option<(u32, u32, u32)> {
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+        let field = |name: &str| -> Option<u32> {
+            cpuinfo
+                .lines()
+                .take_while(|line| !line.trim().is_empty())
+                .find_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    (key.trim() == name).then(|| value.trim().parse().ok())?
+                })
+        };
+        Some((field("cpu family")?, field("model")?, field("stepping")?))
+    }
+
+    /// Reads the family, model and stepping from `PROCESSOR_IDENTIFIER`,
+    /// e.g. `Intel64 Family 6 Model 158 Stepping 10, GenuineIntel`.
+    #[cfg(windows)]
+    fn signature() -> Option<(u32, u32, u32)> {
+        let identifier = env::var("PROCESSOR_IDENTIFIER").ok()?;
+        let words: Vec<&str> = identifier.split(|c| c == ' ' || c == ',').collect();
+        let field = |name: &str| -> Option<u32> {
+            let position = words.iter().position(|&word| word == name)?;
+            words.get(position + 1)?.parse().ok()
+        };
+        Some((field("Family")?, field("Model")?, field("Stepping")?))
+    }
+
+    /// The signature isn't read on other platforms.
+    #[cfg(not(any(target_os = "linux", windows)))]
+    fn signature() -> Option<(u32, u32, u32)> {
+        None
+    }
+
+    /// Renders the usage of every logical core as a table wrapped to the terminal width.
+    fn core_usage(config: &Config, sys: &System) -> Vec<String> {
+        let cells: Vec<String> = sys
+            .cpus()
+            .iter()
+            .enumerate()
+            .map(|(i, cpu)| format!("Core {}: {:.0}%", i, cpu.cpu_usage()))
+            .collect();
+        let cell_width = cells.iter().map(String::len).max().unwrap_or(0) + 2;
+        let per_row = (config.line_width().unwrap_or(80) / cell_width).max(1);
+
+        cells
+            .chunks(per_row)
+            .map(|row| {
+                let row: String = row
+                    .iter()
+                    .map(|cell| format!("{:width$}", cell, width = cell_width))
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+/// The CPU package temperature, the one reading most people look for.
+pub struct CpuTemp;
+
+impl Module for CpuTemp {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let celsius = CpuTemp::detect(sys).map(|component| component.temperature());
+        let lines = celsius
+            .map(|celsius| {
+                Line::field(
+                    config.label("cpu_temp", "CPU Temp"),
+                    config.paint(
+                        usage_color(f64::from(celsius), (config.temp_warn, config.temp_crit))
+                            .normal(),
+                        config.temperature_unit.format(celsius),
+                    ),
+                )
+            })
+            .into_iter()
+            .collect();
+        Output::new(lines, vec![("cpu_temperature", celsius.into())])
+    }
+}
+
+impl CpuTemp {
+    /// Picks the Intel package, AMD Tctl or any other coretemp sensor, in that order.
+    pub fn detect(sys: &System) -> Option<&Component> {
+        ["Package", "Tctl", "coretemp"].iter().find_map(|name| {
+            sys.components()
+                .iter()
+                .find(|component| component.label().contains(name))
+        })
+    }
+}
+
+/// The total size of each level of CPU cache from L2 on, e.g. `L2 4.00 MiB, L3 32.00 MiB`.
+pub struct CpuCache;
+
+impl Module for CpuCache {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let caches = CpuCache::detect();
+        let sizes = caches
+            .iter()
+            .map(|(level, bytes)| (format!("l{}", level), (*bytes).into()))
+            .collect();
+        let values = vec![("cpu_cache", Value::Object(sizes))];
+        if caches.is_empty() {
+            return Output::new(Vec::new(), values);
+        }
+
+        let caches: Vec<String> = caches
+            .iter()
+            .map(|(level, bytes)| {
+                format!(
+                    "L{} {}",
+                    level,
+                    humanize(*bytes, &MemType::Auto, config.precision)
+                )
+            })
+            .collect();
+        let lines = vec![Line::field(
+            config.label("cpu_cache", "Cache"),
+            caches.join(", "),
+        )];
+        Output::new(lines, values)
+    }
+}
+
+impl CpuCache {
+    /// Sums the caches of every core by level from `/sys/devices/system/cpu`, counting
+    /// caches shared by several cores once. Returns `(level, bytes)` from L2 on, in order.
+    #[cfg(target_os = "linux")]
+    pub fn detect() -> Vec<(u8, u64)> {
+        let cpus = match std::fs::read_dir("/sys/devices/system/cpu") {
+            Ok(cpus) => cpus,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut seen = Vec::new();
+        let mut totals: Vec<(u8, u64)> = Vec::new();
+        for index in cpus
+            .flatten()
+            .filter_map(|cpu| std::fs::read_dir(cpu.path().join("cache")).ok())
+            .flat_map(|indexes| indexes.flatten())
+        {
+            let read = |file: &str| std::fs::read_to_string(index.path().join(file)).ok();
+            let (level, size, shared) = match (read("level"), read("size"), read("shared_cpu_list"))
+            {
+                (Some(level), Some(size), Some(shared)) => (level, size, shared),
+                _ => continue,
+            };
+            let level: u8 = match level.trim().parse() {
+                Ok(level) if level >= 2 => level,
+                _ => continue,
+            };
+            let bytes = match CpuCache::parse_size(size.trim()) {
+                Some(bytes) => bytes,
+                None => continue,
+            };
+
+            // Every core sharing a cache lists it, with the same cores
+            let key = (level, read("type").unwrap_or_default(), shared);
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key);
+            match totals.iter_mut().find(|(known, _)| *known == level) {
+                Some((_, total)) => *total += bytes,
+                None => totals.push((level, bytes)),
+            }
+        }
+
+        totals.sort_unstable();
+        totals
+    }
+
+    /// Reads the L2 and L3 sizes from `wmic`, which reports them in KB.
+    /// Returns `(level, bytes)` in order.
+    #[cfg(windows)]
+    pub fn detect() -> Vec<(u8, u64)> {
+        let out = match Command::new("wmic")
+            .args(["cpu", "get", "L2CacheSize,L3CacheSize", "/value"])
+            .output()
+        {
+            Ok(out) if out.status.success() => out,
+            _ => return Vec::new(),
+        };
+
+        // Every processor package prints its own `L2CacheSize=4096` style lines
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        let mut totals: Vec<(u8, u64)> = Vec::new();
+        for (level, key) in [(2, "L2CacheSize"), (3, "L3CacheSize")] {
+            let kilobytes: u64 = stdout
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix(key)?.strip_prefix('='))
+                .filter_map(|value| value.trim().parse::<u64>().ok())
+                .sum();
+            if kilobytes > 0 {
+                totals.push((level, kilobytes * 1024));
+            }
+        }
+        totals
+    }
+
+    /// Cache sizes aren't read on other platforms.
+    #[cfg(not(any(target_os = "linux", windows)))]
+    pub fn detect() -> Vec<(u8, u64)> {
+        Vec::new()
+    }
+
+    /// Parses a sysfs cache size such as `32K` or `8M` into bytes.
+    #[cfg(target_os = "linux")]
+    fn parse_size(size: &str) -> Option<u64> {
+        let (number, multiplier) = match size.chars().last()? {
+            'K' => (&size[..size.len() - 1], 1024),
+            'M' => (&size[..size.len() - 1], 1024 * 1024),
+            'G' => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+            _ => (size, 1),
+        };
+        Some(number.parse::<u64>().ok()? * multiplier)
+    }
+}
+
+pub struct Gpu;
+
+impl Module for Gpu {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let gpus = Gpu::detect();
+        let lines = gpus
+            .iter()
+            .map(|gpu| Line::field(config.label("gpu", "GPU"), gpu))
+            .collect();
+        Output::new(lines, vec![("gpus", gpus.into())])
+    }
+}
+
+impl Gpu {
+    /// Gets the names of all graphics adapters found on the system.
+    /// Returns an empty list if the platform tool is missing or reports nothing.
+    pub fn detect() -> Vec<String> {
+        let out = if cfg!(target_os = "windows") {
+            Command::new("wmic")
+                .args(["path", "win32_VideoController", "get", "name"])
+                .output()
+        } else if cfg!(target_os = "macos") {
+            Command::new("system_profiler")
+                .arg("SPDisplaysDataType")
+                .output()
+        } else {
+            Command::new("lspci").output()
+        };
+
+        let out = match out {
+            Ok(out) if out.status.success() => out,
+            _ => return Vec::new(),
+        };
+        let stdout = String::from_utf8_lossy(&out.stdout);
+
+        if cfg!(target_os = "windows") {
+            // First line is the "Name" header.
+            stdout
+                .lines()
+                .skip(1)
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect()
+        } else if cfg!(target_os = "macos") {
+            stdout
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("Chipset Model:"))
+                .map(|name| name.trim().to_string())
+                .collect()
+        } else {
+            stdout
+                .lines()
+                .filter(|line| {
+                    line.contains("VGA compatible controller")
+                        || line.contains("3D controller")
+                        || line.contains("Display controller")
+                })
+                .filter_map(|line| line.splitn(3, ':').nth(2))
+                .map(Gpu::format_pci_name)
+                .collect()
+        }
+    }
+
+    /// Turns an lspci device description such as
+    /// `NVIDIA Corporation GA106 [GeForce RTX 3060] (rev a1)` into `NVIDIA GeForce RTX 3060`.
+    ///
+    /// The marketing name is the last bracketed part. The vendor is shortened for the big
+    /// three, whose company names come first, and is the first word for everyone else.
+    fn format_pci_name(desc: &str) -> String {
+        let desc = match desc.rfind(" (rev") {
+            Some(idx) => &desc[..idx],
+            None => desc,
+        }
+        .trim();
+
+        let known = [
+            ("Advanced Micro Devices, Inc.", "AMD"),
+            ("NVIDIA Corporation", "NVIDIA"),
+            ("Intel Corporation", "Intel"),
+        ]
+        .iter()
+        .find_map(|&(company, vendor)| Some((vendor, desc.strip_prefix(company)?.trim_start())));
+        let (vendor, rest) = match known {
+            // AMD tags its devices, e.g. `[AMD/ATI] Navi 21 [Radeon RX 6800]`
+            Some((vendor, rest)) => match rest
+                .strip_prefix('[')
+                .and_then(|tagged| tagged.split_once(']'))
+            {
+                Some((_, untagged)) => (vendor, untagged.trim_start()),
+                None => (vendor, rest),
+            },
+            None => match desc.split_once(' ') {
+                Some((vendor, _)) => (vendor, desc),
+                None => return desc.to_string(),
+            },
+        };
+
+        match (rest.rfind('['), rest.rfind(']')) {
+            (Some(start), Some(end)) if start < end => {
+                format!("{} {}", vendor, &rest[start + 1..end])
+            }
+            _ if known.is_some() && !rest.is_empty() => format!("{} {}", vendor, rest),
+            _ => desc.to_string(),
+        }
+    }
+}
+
+pub struct Resolution;
+
+impl Module for Resolution {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let resolutions = Resolution::detect();
+        let lines = if resolutions.is_empty() {
+            Vec::new()
+        } else {
+            vec![Line::field(
+                config.label("resolution", "Resolution"),
+                resolutions.join(", "),
+            )]
+        };
+        Output::new(lines, vec![("resolutions", resolutions.into())])
+    }
+}
+
+impl Resolution {
+    /// Gets the resolution of every connected display as `WIDTHxHEIGHT`.
+    /// Returns an empty list on headless systems.
+    #[cfg(windows)]
+    pub fn detect() -> Vec<String> {
+        use winapi::um::winuser::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+
+        // Only the primary display is reported by GetSystemMetrics.
+        let (width, height) =
+            unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) };
+        if width > 0 && height > 0 {
+            vec![format!("{}x{}", width, height)]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Gets the resolution of every connected display as `WIDTHxHEIGHT`.
+    /// Returns an empty list on headless systems.
+    #[cfg(not(windows))]
+    pub fn detect() -> Vec<String> {
+        let run = |program: &str, args: &[&str]| -> Option<String> {
+            let out = Command::new(program).args(args).output().ok()?;
+            if out.status.success() {
+                Some(String::from_utf8_lossy(&out.stdout).to_string())
+            } else {
+                None
+            }
+        };
+
+        if cfg!(target_os = "macos") {
+            return run("system_profiler", &["SPDisplaysDataType"])
+                .map(|stdout| {
+                    stdout
+                        .lines()
+                        .filter_map(|line| line.trim().strip_prefix("Resolution:"))
+                        .filter_map(|res| {
+                            let mut nums = res
+                                .split(|c: char| !c.is_ascii_digit())
+                                .filter(|n| !n.is_empty());
+                            Some(format!("{}x{}", nums.next()?, nums.next()?))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            if let Some(stdout) = run("swaymsg", &["-t", "get_outputs", "--raw"]) {
+                let resolutions = Resolution::parse_sway_outputs(&stdout);
+                if !resolutions.is_empty() {
+                    return resolutions;
+                }
+            }
+        }
+
+        // On Wayland without sway this still covers XWayland.
+        run("xrandr", &["--current"])
+            .map(|stdout| {
+                stdout
+                    .lines()
+                    .filter(|line| line.contains('*'))
+                    .filter_map(|line| line.split_whitespace().next())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Pulls the `current_mode` width and height of each output out of `swaymsg -t get_outputs`.
+    #[cfg(not(windows))]
+    fn parse_sway_outputs(json: &str) -> Vec<String> {
+        let number_after = |text: &str, key: &str| -> Option<String> {
+            let rest = &text[text.find(key)? + key.len()..];
+            let rest = rest.trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            Some(rest[..end].to_string()).filter(|n| !n.is_empty())
+        };
+
+        json.split("\"current_mode\"")
+            .skip(1)
+            .filter_map(|mode| {
+                let mode = &mode[..mode.find('}')?];
+                Some(format!(
+                    "{}x{}",
+                    number_after(mode, "\"width\"")?,
+                    number_after(mode, "\"height\"")?
+                ))
+            })
+            .collect()
+    }
+}
+
+pub struct Memory;
+
+impl Module for Memory {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let mut memory = format!(
+            "{} / {}",
+            humanize(sys.used_memory(), &config.memory_type, config.precision),
+            humanize(sys.total_memory(), &config.memory_type, config.precision)
+        );
+        if config.memory_bar.unwrap_or(config.usage_bars) {
+            if let Some(bar) = config.render_bar(
+                sys.used_memory(),
+                sys.total_memory(),
+                config.bar_width,
+                MEMORY_THRESHOLDS,
+            ) {
+                memory.push_str(&format!(" {}", bar));
+            }
+        } else if config.show_memory_percent {
+            if let Some(percent) = percent(sys.used_memory(), sys.total_memory()) {
+                memory.push_str(&format!(" ({:.0}%)", percent));
+            }
+        }
+        if config.memory_detailed {
+            // Cache counts as used but can be reclaimed, so available is usually more than free.
+            memory.push_str(&format!(
+                ", {} available, {} free",
+                humanize(
+                    sys.available_memory(),
+                    &config.memory_type,
+                    config.precision
+                ),
+                humanize(sys.free_memory(), &config.memory_type, config.precision)
+            ));
+        }
+
+        Output::new(
+            vec![Line::field(config.label("memory", "Memory"), memory)],
+            vec![
+                ("memory_used", sys.used_memory().into()),
+                ("memory_total", sys.total_memory().into()),
+                ("memory_available", sys.available_memory().into()),
+                ("memory_free", sys.free_memory().into()),
+            ],
+        )
+    }
+}
+
+/// Used and total swap. Skips when there is no swap, unless `force_swap` is set.
+pub struct Swap;
+
+impl Module for Swap {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let values = vec![
+            ("swap_used", sys.used_swap().into()),
+            ("swap_total", sys.total_swap().into()),
+            ("swap_free", sys.free_swap().into()),
+        ];
+        if sys.total_swap() == 0 && !config.force_swap {
+            return Output::new(Vec::new(), values);
+        }
+
+        let label = config.label("swap", "Swap");
+        if config.swap_percent_only {
+            // No swap at all is no swap used, not a division by zero
+            let percent = percent(sys.used_swap(), sys.total_swap()).unwrap_or(0.0);
+            return Output::new(vec![Line::field(label, format!("{:.0}%", percent))], values);
+        }
+
+        let unit = config.swap_type.as_ref().unwrap_or(&config.memory_type);
+        let mut swap = format!(
+            "{} / {}",
+            humanize(sys.used_swap(), unit, config.precision),
+            humanize(sys.total_swap(), unit, config.precision)
+        );
+        if config.swap_bar.unwrap_or(config.usage_bars) {
+            if let Some(bar) = config.render_bar(
+                sys.used_swap(),
+                sys.total_swap(),
+                config.bar_width,
+                MEMORY_THRESHOLDS,
+            ) {
+                swap.push_str(&format!(" {}", bar));
+            }
+        } else if config.show_memory_percent {
+            if let Some(percent) = percent(sys.used_swap(), sys.total_swap()) {
+                swap.push_str(&format!(" ({:.0}%)", percent));
+            }
+        }
+        if config.memory_detailed {
+            swap.push_str(&format!(
+                ", {} free",
+                humanize(sys.free_swap(), unit, config.precision)
+            ));
+        }
+
+        Output::new(vec![Line::field(label, swap)], values)
+    }
+}
+
+pub struct LocalIp;
+
+impl Module for LocalIp {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let ip = LocalIp::detect();
+        let lines = ip
+            .iter()
+            .map(|ip| Line::field(config.label("local_ip", "Local IP"), ip))
+            .collect();
+        Output::new(lines, vec![("local_ip", ip.into())])
+    }
+}
+
+impl LocalIp {
+    /// Gets the IPv4 address of the interface that routes to the internet.
+    ///
+    /// Connecting a UDP socket only picks the route, nothing is sent. Returns `None` when offline.
+    pub fn detect() -> Option<String> {
+        let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+        socket.connect("8.8.8.8:80").ok()?;
+        let ip = socket.local_addr().ok()?.ip();
+
+        if ip.is_unspecified() || ip.is_loopback() {
+            None
+        } else {
+            Some(ip.to_string())
+        }
+    }
+}
+
+/// Bytes received and transmitted by every used interface except loopback since boot.
+pub struct Network;
+
+impl Module for Network {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let interfaces = Network::interfaces(sys);
+        let lines = interfaces
+            .iter()
+            .map(|(name, data)| {
+                let traffic = match config.traffic {
+                    Traffic::Totals => format!(
+                        "↓ {} ↑ {}",
+                        humanize(data.total_received(), &MemType::Auto, config.precision),
+                        humanize(data.total_transmitted(), &MemType::Auto, config.precision)
+                    ),
+                    Traffic::Rates(Some(elapsed)) => {
+                        let (received, transmitted) = Network::rates(data, elapsed);
+                        format!(
+                            "↓ {}/s ↑ {}/s",
+                            humanize(received, &MemType::Auto, config.precision),
+                            humanize(transmitted, &MemType::Auto, config.precision)
+                        )
+                    }
+                    // There is no earlier sample to compare with yet.
+                    Traffic::Rates(None) => String::from("↓ -- ↑ --"),
+                };
+                Line::field(name.as_str(), traffic)
+            })
+            .collect();
+
+        let values = interfaces
+            .iter()
+            .map(|(name, data)| {
+                let mut interface = vec![
+                    ("name", name.as_str().into()),
+                    ("received", data.total_received().into()),
+                    ("transmitted", data.total_transmitted().into()),
+                ];
+                if let Traffic::Rates(Some(elapsed)) = config.traffic {
+                    let (received, transmitted) = Network::rates(data, elapsed);
+                    interface.push(("received_per_second", received.into()));
+                    interface.push(("transmitted_per_second", transmitted.into()));
+                }
+                Value::object(interface)
+            })
+            .collect();
+        Output::new(lines, vec![("network", Value::Array(values))])
+    }
+}
+
+impl Network {
+    /// Returns the non-loopback interfaces that carried any data, sorted by name.
+    pub fn interfaces(sys: &System) -> Vec<(&String, &NetworkData)> {
+        let mut interfaces: Vec<_> = sys
+            .networks()
+            .iter()
+            .filter(|(name, _)| !name.starts_with("lo") && !name.contains("Loopback"))
+            .filter(|(_, data)| data.total_received() > 0 || data.total_transmitted() > 0)
+            .collect();
+        interfaces.sort_by_key(|(name, _)| *name);
+        interfaces
+    }
+
+    /// Returns the bytes received and transmitted per second over the `elapsed` time
+    /// since the previous refresh.
+    pub fn rates(data: &NetworkData, elapsed: Duration) -> (u64, u64) {
+        let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+        (
+            (data.received() as f64 / seconds) as u64,
+            (data.transmitted() as f64 / seconds) as u64,
+        )
+    }
+}
+
+pub struct Battery;
+
+impl Module for Battery {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        let batteries = Battery::detect();
+        let lines = batteries
+            .iter()
+            .map(|(capacity, status)| {
+                Line::field(
+                    config.label("battery", "Battery"),
+                    format!("{}% ({})", capacity, status),
+                )
+            })
+            .collect();
+
+        let values = batteries
+            .into_iter()
+            .map(|(capacity, status)| {
+                Value::object(vec![
+                    ("capacity", u64::from(capacity).into()),
+                    ("status", status.into()),
+                ])
+            })
+            .collect();
+        Output::new(lines, vec![("batteries", Value::Array(values))])
+    }
+}
+
+impl Battery {
+    /// Gets the charge percentage and charging state of every battery.
+    /// Returns an empty list on machines without one.
+    #[cfg(windows)]
+    pub fn detect() -> Vec<(u8, String)> {
+        use winapi::um::winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return Vec::new();
+        }
+
+        // 128 means there is no battery, 255 means its status is unknown.
+        if status.BatteryFlag & 128 != 0 || status.BatteryLifePercent > 100 {
+            return Vec::new();
+        }
+
+        let state = if status.BatteryFlag & 8 != 0 {
+            "Charging"
+        } else if status.ACLineStatus == 1 {
+            "Plugged in"
+        } else {
+            "Discharging"
+        };
+        vec![(status.BatteryLifePercent, String::from(state))]
+    }
+
+    /// Gets the charge percentage and charging state of every battery.
+    /// Returns an empty list on machines without one.
+    #[cfg(not(windows))]
+    pub fn detect() -> Vec<(u8, String)> {
+        if cfg!(target_os = "macos") {
+            let out = match Command::new("pmset").args(["-g", "batt"]).output() {
+                Ok(out) if out.status.success() => out,
+                _ => return Vec::new(),
+            };
+
+            // e.g. " -InternalBattery-0 (id=4653155)	82%; charging; 1:05 remaining present: true"
+            return String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .filter(|line| line.contains("InternalBattery"))
+                .filter_map(|line| {
+                    let mut fields = line.split('\t').nth(1)?.split(';').map(str::trim);
+                    let capacity = fields.next()?.trim_end_matches('%').parse().ok()?;
+                    let mut state = fields.next()?.to_string();
+                    if let Some(first) = state.get_mut(0..1) {
+                        first.make_ascii_uppercase();
+                    }
+                    Some((capacity, state))
+                })
+                .collect();
+        }
+
+        let entries = match std::fs::read_dir("/sys/class/power_supply") {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut batteries: Vec<_> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map_or(false, |name| name.to_string_lossy().starts_with("BAT"))
+            })
+            .collect();
+        batteries.sort();
+
+        batteries
+            .iter()
+            .filter_map(|path| {
+                let capacity = std::fs::read_to_string(path.join("capacity")).ok()?;
+                let status = std::fs::read_to_string(path.join("status")).ok()?;
+                Some((capacity.trim().parse().ok()?, status.trim().to_string()))
+            })
+            .collect()
+    }
+}
+
+/// The temperature section, listing every sensor below its own header.
+/// Skips when there are no readings.
+pub struct Temperature;
+
+impl Module for Temperature {
+    fn gather(&self, config: &Config, sys: &System) -> Output {
+        let components = Temperature::shown(config, sys);
+        let gpus = Temperature::gpus(config);
+
+        let mut readings = Vec::new();
+        let thresholds = (config.temp_warn, config.temp_crit);
+        for component in &components {
+            readings.push(Line::Raw(format!(
+                "{}: {}",
+                config.paint_label(component.label()),
+                config.paint(
+                    usage_color(f64::from(component.temperature()), thresholds).normal(),
+                    config.temperature_unit.format(component.temperature())
+                )
+            )));
+        }
+        for gpu in &gpus {
+            let mut reading = config.paint(
+                usage_color(f64::from(gpu.celsius), thresholds).normal(),
+                config.temperature_unit.format(gpu.celsius),
+            );
+            if let Some(rpm) = gpu.fan_rpm {
+                reading.push_str(&format!(", fan {} RPM", rpm));
+            } else if let Some(percent) = gpu.fan_percent {
+                reading.push_str(&format!(", fan {}%", percent));
+            }
+            readings.push(Line::Raw(format!(
+                "{}: {}",
+                config.paint_label(&gpu.label),
+                reading
+            )));
+        }
+
+        // Sensors sit in their own section, so their labels aren't aligned with the fields.
+        let mut lines = Vec::new();
+        if !readings.is_empty() {
+            lines.push(Line::Raw(String::new()));
+            lines.push(Line::Raw(config.paint(Red.bold(), "Temperature")));
+            lines.push(Line::Raw(config.paint(Red.bold(), "-".repeat(20))));
+            lines.extend(readings);
+            lines.push(Line::Raw(String::new()));
+        }
+
+        let temps = components
+            .iter()
+            .map(|component| {
+                Value::object(vec![
+                    ("label", component.label().into()),
+                    ("celsius", component.temperature().into()),
+                ])
+            })
+            .collect();
+        let gpus = gpus
+            .into_iter()
+            .map(|gpu| {
+                Value::object(vec![
+                    ("label", gpu.label.into()),
+                    ("celsius", gpu.celsius.into()),
+                    ("fan_rpm", gpu.fan_rpm.map(u64::from).into()),
+                    ("fan_percent", gpu.fan_percent.map(u64::from).into()),
+                ])
+            })
+            .collect();
+        Output::new(
+            lines,
+            vec![
+                ("temperatures", Value::Array(temps)),
+                ("gpu_temperatures", Value::Array(gpus)),
+            ],
+        )
+    }
+}
+
+/// A GPU's thermal reading, which sysinfo's components often miss.
+pub struct GpuSensor {
+    pub label: String,
+    pub celsius: f32,
+    /// Fan speed in RPM, as AMD cards report it.
+    pub fan_rpm: Option<u32>,
+    /// Fan speed in percent of its maximum, as NVIDIA cards report it.
+    pub fan_percent: Option<u32>,
+}
+
+impl Temperature {
+    /// Returns the sensors whose label contains one of the `temp_filter` entries,
+    /// all of them when the filter is empty.
+    pub fn shown<'a>(config: &Config, sys: &'a System) -> Vec<&'a Component> {
+        sys.components()
+            .iter()
+            .filter(|component| {
+                config.temp_filter.is_empty()
+                    || config
+                        .temp_filter
+                        .iter()
+                        .any(|filter| component.label().contains(filter.as_str()))
+            })
+            .collect()
+    }
+}
+
+impl Temperature {
+    /// Reads the GPUs' sensors from `nvidia-smi` and the amdgpu hwmon files, filtered
+    /// like the other sensors. Empty when there is no discrete GPU. Linux only.
+    pub fn gpus(config: &Config) -> Vec<GpuSensor> {
+        if !cfg!(target_os = "linux") {
+            return Vec::new();
+        }
+
+        let mut gpus = Temperature::nvidia();
+        gpus.extend(Temperature::amd());
+        gpus.retain(|gpu| {
+            config.temp_filter.is_empty()
+                || config
+                    .temp_filter
+                    .iter()
+                    .any(|filter| gpu.label.contains(filter.as_str()))
+        });
+        gpus
+    }
+
+    /// Asks `nvidia-smi` for every NVIDIA GPU's temperature and fan speed.
+    fn nvidia() -> Vec<GpuSensor> {
+        let out = match Command::new("nvidia-smi")
+            .args([
+                "--query-gpu=name,temperature.gpu,fan.speed",
+                "--format=csv,noheader,nounits",
+            ])
+            .output()
+        {
+            Ok(out) if out.status.success() => out,
+            _ => return Vec::new(),
+        };
+
+        String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut columns = line.split(',').map(str::trim);
+                let name = columns.next()?;
+                let celsius = columns.next()?.parse().ok()?;
+                // Passively cooled cards report "[N/A]".
+                let fan_percent = columns.next().and_then(|fan| fan.parse().ok());
+                Some(GpuSensor {
+                    label: name.to_string(),
+                    celsius,
+                    fan_rpm: None,
+                    fan_percent,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads `temp1_input` and `fan1_input` from the hwmon directory of every
+    /// `/sys/class/drm/card*` that has one.
+    fn amd() -> Vec<GpuSensor> {
+        let cards = match std::fs::read_dir("/sys/class/drm") {
+            Ok(cards) => cards,
+            Err(_) => return Vec::new(),
+        };
+        let mut cards: Vec<_> = cards
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                // card0-DP-1 and the like are the card's connectors.
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.strip_prefix("card"))
+                    .map_or(false, |id| id.chars().all(|c| c.is_ascii_digit()))
+            })
+            .collect();
+        cards.sort();
+
+        let read = |path: &Path| -> Option<String> {
+            Some(std::fs::read_to_string(path).ok()?.trim().to_string())
+        };
+        cards
+            .iter()
+            .filter_map(|card| {
+                let hwmon = std::fs::read_dir(card.join("device/hwmon"))
+                    .ok()?
+                    .flatten()
+                    .next()?
+                    .path();
+                let millidegrees: f32 = read(&hwmon.join("temp1_input"))?.parse().ok()?;
+                let name = read(&hwmon.join("name")).unwrap_or_else(|| String::from("GPU"));
+                let card = card.file_name()?.to_string_lossy();
+                Some(GpuSensor {
+                    label: format!("{} ({})", name, card),
+                    celsius: millidegrees / 1000.0,
+                    fan_rpm: read(&hwmon.join("fan1_input")).and_then(|rpm| rpm.parse().ok()),
+                    fan_percent: None,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The color blocks, left out when colored output is off.
+pub struct Colors;
+
+impl Module for Colors {
+    fn gather(&self, config: &Config, _sys: &System) -> Output {
+        if !config.colored_output {
+            return Output::default();
+        }
+
+        let rows: Vec<Vec<ansi_term::Color>> = match &config.color_palette {
+            Some(palette) => vec![palette
+                .iter()
+                .filter_map(|name| parse_color(name))
+                .collect()],
+            None if config.show_bright_colors => vec![
+                vec![Red, Green, Blue, Yellow],
+                vec![Black, White, Purple, Cyan],
+            ],
+            None => vec![vec![Red, Green, Blue, Yellow]],
+        };
+        let paint_row = |row: &[ansi_term::Color], block: &str| -> String {
+            row.iter()
+                .map(|&color| config.paint(color.on(color), block))
+                .collect()
+        };
+
+        if config.colors_compact {
+            return Output::new(
+                vec![Line::Raw(paint_row(&rows.concat(), "   "))],
+                Vec::new(),
+            );
+        }
+
+        let block = " ".repeat(config.colors_width * 2 + 1);
+        // Rows too wide for the screen wrap onto the next line
+        let per_row = config
+            .line_width()
+            .map_or(usize::MAX, |width| (width / block.len()).max(1));
+        let lines = rows
+            .iter()
+            .flat_map(|row| row.chunks(per_row))
+            .flat_map(|row| {
+                let line = paint_row(row, &block);
+                (0..config.colors_height).map(move |_| Line::Raw(line.clone()))
+            })
+            .collect();
+        Output::new(lines, Vec::new())
+    }
+}
+
+/// Formats seconds as e.g. `2d 4h 13m`, leaving out units that are zero.
+/// Durations under a minute are shown in seconds.
+fn format_duration(secs: u64) -> String {
+    if secs < 60 {
+        return format!("{}s", secs);
+    }
+
+    let units = [
+        (secs / 86400, "d"),
+        (secs % 86400 / 3600, "h"),
+        (secs % 3600 / 60, "m"),
+    ];
+    units
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Formats seconds as e.g. `3 days, 4 hours, 12 mins`, leaving out units that are zero.
+/// Minutes are rounded to the nearest one, durations under a minute are shown in seconds.
+fn format_human_duration(secs: u64) -> String {
+    if secs < 60 {
+        return plural(secs, "sec");
+    }
+
+    let minutes = (secs + 30) / 60;
+    let units = [
+        (minutes / 1440, "day"),
+        (minutes % 1440 / 60, "hour"),
+        (minutes % 60, "min"),
+    ];
+    units
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| plural(*value, unit))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats e.g. `1 day` or `2 days`.
+fn plural(value: u64, unit: &str) -> String {
+    if value == 1 {
+        format!("{} {}", value, unit)
+    } else {
+        format!("{} {}s", value, unit)
+    }
+}
+
+/// Returns the width of the terminal stdout is printed to, or `$COLUMNS` if stdout
+/// isn't a terminal. Returns `None` if neither is known.
+pub fn terminal_width() -> Option<usize> {
+    terminal_columns().or_else(|| {
+        env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+    })
+}
+
+/// Asks the terminal attached to stdout for its number of columns.
+#[cfg(unix)]
+fn terminal_columns() -> Option<usize> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+/// Asks the console attached to stdout for the width of its window.
+#[cfg(windows)]
+fn terminal_columns() -> Option<usize> {
+    use winapi::um::processenv::GetStdHandle;
+    use winapi::um::winbase::STD_OUTPUT_HANDLE;
+    use winapi::um::wincon::{GetConsoleScreenBufferInfo, CONSOLE_SCREEN_BUFFER_INFO};
+
+    let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { std::mem::zeroed() };
+    if unsafe { GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), &mut info) } == 0 {
+        return None;
+    }
+    let columns = info.srWindow.Right - info.srWindow.Left + 1;
+    usize::try_from(columns).ok().filter(|&columns| columns > 0)
+}
+
+/// Cuts `text` to `width` visible characters, ending it in `…` if anything was cut.
+/// Escape sequences don't count towards the width, and colors are reset after a cut.
+pub fn truncate(text: &str, width: usize) -> String {
+    if strip_ansi(text).chars().count() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::with_capacity(text.len());
+    let mut visible = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Copy the whole sequence, it takes no space on screen
+            truncated.push(c);
+            if let Some(next) = chars.next() {
+                truncated.push(next);
+                if next == '[' {
+                    for c in chars.by_ref() {
+                        truncated.push(c);
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+            }
+            continue;
+        }
+        if visible + 1 >= width {
+            break;
+        }
+        truncated.push(c);
+        visible += 1;
+    }
+
+    if width > 0 {
+        truncated.push('…');
+    }
+    if text.contains('\x1b') {
+        truncated.push_str("\x1b[0m");
+    }
+    truncated
+}
+
+/// Removes ANSI escape sequences such as colors from `text`.
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            stripped.push(c);
+            continue;
+        }
+        // CSI sequences like `ESC[1;31m` end at their first letter-ish byte.
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+
+    stripped
+}
+
+/// Returns `used` as a percentage of `total`, or `None` if `total` is zero.
+fn percent(used: u64, total: u64) -> Option<f64> {
+    if total == 0 {
+        None
+    } else {
+        Some(used as f64 / total as f64 * 100.0)
+    }
+}
+
+/// Formats a byte count in the given unit with `precision` decimals, e.g. `2.45 GB`.
+///
+/// [MemType::Auto] picks the binary unit that keeps the value between 1 and 1024.
+fn humanize(bytes: u64, mem_type: &MemType, precision: usize) -> String {
+    let (divisor, unit) = match mem_type {
+        MemType::KB => (1e+3, "KB"),
+        MemType::MB => (1e+6, "MB"),
+        MemType::GB => (1e+9, "GB"),
+        MemType::TB => (1e+12, "TB"),
+        MemType::KiB => (1024.0, "KiB"),
+        MemType::MiB => (1024.0 * 1024.0, "MiB"),
+        MemType::GiB => (1024.0 * 1024.0 * 1024.0, "GiB"),
+        MemType::Auto => {
+            let units = [
+                (1024.0 * 1024.0 * 1024.0 * 1024.0, "TiB"),
+                (1024.0 * 1024.0 * 1024.0, "GiB"),
+                (1024.0 * 1024.0, "MiB"),
+            ];
+            units
+                .iter()
+                .copied()
+                .find(|(divisor, _)| bytes as f64 >= *divisor)
+                .unwrap_or((1024.0, "KiB"))
+        }
+    };
+
+    format!("{:.*} {}", precision, bytes as f64 / divisor, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pci_names_keep_the_vendor_and_the_marketing_name() {
+        let cases = [
+            (
+                "NVIDIA Corporation GA106 [GeForce RTX 3060] (rev a1)",
+                "NVIDIA GeForce RTX 3060",
+            ),
+            (
+                "NVIDIA Corporation TU117M [GeForce GTX 1650 Mobile / Max-Q] (rev a1)",
+                "NVIDIA GeForce GTX 1650 Mobile / Max-Q",
+            ),
+            (
+                "Advanced Micro Devices, Inc. [AMD/ATI] Navi 21 [Radeon RX 6800/6800 XT / 6900 XT] (rev c1)",
+                "AMD Radeon RX 6800/6800 XT / 6900 XT",
+            ),
+            (
+                "Advanced Micro Devices, Inc. [AMD/ATI] Device 73bf",
+                "AMD Device 73bf",
+            ),
+            (
+                "Intel Corporation TigerLake-LP GT2 [Iris Xe Graphics] (rev 01)",
+                "Intel Iris Xe Graphics",
+            ),
+            (
+                "Intel Corporation UHD Graphics 620 (rev 07)",
+                "Intel UHD Graphics 620",
+            ),
+            (
+                "Red Hat, Inc. Virtio 1.0 GPU (rev 01)",
+                "Red Hat, Inc. Virtio 1.0 GPU",
+            ),
+        ];
+
+        for (desc, name) in cases {
+            assert_eq!(Gpu::format_pci_name(desc), name);
+        }
+    }
+
+    #[test]
+    fn truncate_cuts_visible_characters_and_keeps_escapes() {
+        assert_eq!(truncate("Arch Linux", 10), "Arch Linux");
+        assert_eq!(truncate("Arch Linux", 6), "Arch …");
+        assert_eq!(truncate("Arch Linux", 1), "…");
+        assert_eq!(truncate("Arch Linux", 0), "");
+        assert_eq!(truncate("ÄÖÜ äöü", 4), "ÄÖÜ…");
+        assert_eq!(
+            truncate("\x1b[1;34mOS\x1b[0m: Arch Linux", 8),
+            "\x1b[1;34mOS\x1b[0m: Arc…\x1b[0m"
+        );
+        assert_eq!(truncate("\x1b[31mred\x1b[0m", 3), "\x1b[31mred\x1b[0m");
+    }
+
+    #[test]
+    fn strip_ansi_removes_escape_sequences() {
+        assert_eq!(strip_ansi("plain"), "plain");
+        assert_eq!(strip_ansi("\x1b[1;34mOS\x1b[0m: Arch"), "OS: Arch");
+        assert_eq!(strip_ansi("\x1b[38;2;255;136;0m█\x1b[0m"), "█");
+    }
+
+    #[test]
+    fn humanize_picks_the_unit() {
+        assert_eq!(humanize(2_450_000_000, &MemType::GB, 2), "2.45 GB");
+        assert_eq!(humanize(1536, &MemType::KiB, 1), "1.5 KiB");
+        assert_eq!(
+            humanize(8 * 1024 * 1024 * 1024, &MemType::MiB, 0),
+            "8192 MiB"
+        );
+        assert_eq!(humanize(512, &MemType::Auto, 2), "0.50 KiB");
+        assert_eq!(humanize(3 * 1024 * 1024, &MemType::Auto, 1), "3.0 MiB");
+        assert_eq!(
+            humanize(16 * 1024 * 1024 * 1024, &MemType::Auto, 2),
+            "16.00 GiB"
+        );
+        assert_eq!(
+            humanize(2 * 1024 * 1024 * 1024 * 1024, &MemType::Auto, 0),
+            "2 TiB"
+        );
+    }
+
+    #[test]
+    fn format_duration_leaves_out_zero_units() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(60), "1m");
+        assert_eq!(format_duration(3600), "1h");
+        assert_eq!(format_duration(86400 + 4 * 3600 + 13 * 60 + 5), "1d 4h 13m");
+        assert_eq!(format_duration(2 * 86400 + 60), "2d 1m");
+    }
+
+    #[test]
+    fn init_names_only_known_systems() {
+        assert_eq!(Init::from_comm("systemd"), Some("systemd"));
+        assert_eq!(Init::from_comm("runit-init"), Some("runit"));
+        assert_eq!(Init::from_comm("openrc-init"), Some("openrc"));
+        assert_eq!(Init::from_comm("s6-svscan"), Some("s6"));
+        assert_eq!(Init::from_comm("dinit"), Some("dinit"));
+        assert_eq!(Init::from_comm("process_api"), None);
+        assert_eq!(Init::from_comm("bash"), None);
+        assert_eq!(Init::from_comm(""), None);
+    }
+
+    #[test]
+    fn current_desktop_skips_the_distribution_prefix() {
+        assert_eq!(Desktop::current("ubuntu:GNOME").as_deref(), Some("GNOME"));
+        assert_eq!(Desktop::current("Pop:GNOME").as_deref(), Some("GNOME"));
+        assert_eq!(Desktop::current("KDE").as_deref(), Some("KDE"));
+        assert_eq!(Desktop::current("XFCE:").as_deref(), Some("XFCE"));
+        assert_eq!(Desktop::current(""), None);
+        assert_eq!(Desktop::current(":"), None);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn sway_outputs_give_the_current_modes() {
+        let json = r#"[
+            {"name": "eDP-1", "modes": [{"width": 800, "height": 600}],
+             "current_mode": {"width": 1920, "height": 1080, "refresh": 60000}},
+            {"name": "HDMI-A-1", "current_mode": {"height":1440,"width":2560}},
+            {"name": "DP-1", "active": false}
+        ]"#;
+        assert_eq!(
+            Resolution::parse_sway_outputs(json),
+            ["1920x1080", "2560x1440"]
+        );
+        assert!(Resolution::parse_sway_outputs("[]").is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn cpu_cache_sizes_parse_their_suffix() {
+        assert_eq!(CpuCache::parse_size("32K"), Some(32 * 1024));
+        assert_eq!(CpuCache::parse_size("8M"), Some(8 * 1024 * 1024));
+        assert_eq!(CpuCache::parse_size("1G"), Some(1024 * 1024 * 1024));
+        assert_eq!(CpuCache::parse_size("512"), Some(512));
+        assert_eq!(CpuCache::parse_size(""), None);
+        assert_eq!(CpuCache::parse_size("K"), None);
+        assert_eq!(CpuCache::parse_size("12X"), None);
+    }
+}
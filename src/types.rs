@@ -1,16 +1,10 @@
+use ansi_term::Color;
 pub use serde::Deserialize;
-
-impl Time {
-    pub fn default() -> Self {
-        Time::Hour
-    }
-}
-
-impl MemType {
-    pub fn default() -> Self {
-        MemType::GB
-    }
-}
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{fmt, io};
 
 /// Returns the default value for bool fields of [Config]
 pub fn default_bool() -> bool {
@@ -27,18 +21,196 @@ pub fn default_usize() -> usize {
     2
 }
 
-#[derive(Deserialize)]
+/// Returns the default character of the separator below the title of [Config]
+pub fn default_separator_char() -> char {
+    '-'
+}
+
+/// Returns the default length of the separator below the title of [Config]
+pub fn default_separator_length() -> usize {
+    30
+}
+
+/// Returns the default width of the usage bars of [Config]
+pub fn default_bar_width() -> usize {
+    20
+}
+
+/// Returns the default number of decimals of the sizes and uptimes of [Config]
+pub fn default_precision() -> usize {
+    2
+}
+
+/// Returns the default mount points of [Config] whose disks are hidden
+pub fn default_disk_exclude() -> Vec<String> {
+    vec![String::from("/snap"), String::from("/boot/efi")]
+}
+
+/// Returns the default disk usage percentage of [Config] colored as a warning
+pub fn default_disk_warn_percent() -> u8 {
+    70
+}
+
+/// Returns the default disk usage percentage of [Config] colored as critical
+pub fn default_disk_crit_percent() -> u8 {
+    90
+}
+
+/// Returns the default temperature of [Config] colored as a warning, in °C
+pub fn default_temp_warn() -> u8 {
+    70
+}
+
+/// Returns the default temperature of [Config] colored as critical, in °C
+pub fn default_temp_crit() -> u8 {
+    85
+}
+
+/// Returns the default format of the dates and times of [Config]
+pub fn default_datetime_format() -> String {
+    String::from("%Y-%m-%d %H:%M")
+}
+
+/// Returns the default label color of [Config]
+pub fn default_label_color() -> String {
+    String::from("blue")
+}
+
+/// Parses a color name such as `"red"` or `"Cyan"`, case-insensitively,
+/// or a hex RGB value such as `"#ff8800"`.
+pub fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::RGB(channel(0)?, channel(2)?, channel(4)?));
+    }
+
+    match name.to_lowercase().as_str() {
+        "black" => Some(Color::Black),
+        "red" => Some(Color::Red),
+        "green" => Some(Color::Green),
+        "yellow" => Some(Color::Yellow),
+        "blue" => Some(Color::Blue),
+        "purple" | "magenta" => Some(Color::Purple),
+        "cyan" => Some(Color::Cyan),
+        "white" => Some(Color::White),
+        _ => None,
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TempUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TryFrom<String> for TempUnit {
+    type Error = String;
+
+    /// Parses `"C"`, `"F"` or `"K"`, case-insensitively.
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "c" => Ok(TempUnit::Celsius),
+            "f" => Ok(TempUnit::Fahrenheit),
+            "k" => Ok(TempUnit::Kelvin),
+            _ => Err(format!(
+                "unknown temperature_unit `{}`, expected one of `C`, `F`, `K`",
+                value
+            )),
+        }
+    }
+}
+
+impl TempUnit {
+    /// Converts `celsius` to this unit and formats it with the unit's suffix.
+    pub fn format(&self, celsius: f32) -> String {
+        match self {
+            TempUnit::Celsius => format!("{:.1}°C", celsius),
+            TempUnit::Fahrenheit => format!("{:.1}°F", celsius * 9.0 / 5.0 + 32.0),
+            TempUnit::Kelvin => format!("{:.1}K", celsius + 273.15),
+        }
+    }
+}
+
+/// How the network module shows the traffic of each interface.
+#[derive(Default)]
+pub enum Traffic {
+    /// Everything received and transmitted since boot.
+    #[default]
+    Totals,
+    /// Bytes per second over the given time since the previous refresh,
+    /// `None` when there is no previous refresh yet.
+    Rates(Option<Duration>),
+}
+
+#[derive(Default, Deserialize)]
+#[serde(try_from = "String")]
 pub enum Time {
     Second,
     Minute,
+    #[default]
     Hour,
+    Day,
+    /// Splits the duration into days, hours and minutes, e.g. `2d 4h 13m`.
+    Auto,
+    /// Like `Auto` but spelled out, e.g. `2 days, 4 hours, 13 mins`.
+    Human,
+    /// Like `Auto` followed by the boot time, e.g. `2d 4h 13m (since 2024-01-28 08:00)`.
+    Both,
 }
 
+impl TryFrom<String> for Time {
+    type Error = String;
+
+    /// Parses the unit case-insensitively, so both `"day"` and `"Day"` work.
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "second" => Ok(Time::Second),
+            "minute" => Ok(Time::Minute),
+            "hour" => Ok(Time::Hour),
+            "day" => Ok(Time::Day),
+            "auto" => Ok(Time::Auto),
+            "human" => Ok(Time::Human),
+            "both" => Ok(Time::Both),
+            _ => Err(format!(
+                "unknown uptime_type `{}`, expected one of `Second`, `Minute`, `Hour`, `Day`, `Auto`, `Human`, `Both`",
+                value
+            )),
+        }
+    }
+}
+
+/// The unit of memory, swap and disk sizes. All three are formatted by the same
+/// helper, so e.g. `GB` always means 10^9 bytes.
 #[derive(Deserialize)]
 pub enum MemType {
+    /// Powers of 1000.
     KB,
     MB,
     GB,
+    TB,
+    /// Powers of 1024.
+    KiB,
+    MiB,
+    GiB,
+    /// Picks the binary unit that keeps the value between 1 and 1024.
+    Auto,
+}
+
+impl Default for MemType {
+    /// `GiB` on Linux, matching `free` and the other native tools, `GB` elsewhere.
+    fn default() -> Self {
+        if cfg!(target_os = "linux") {
+            MemType::GiB
+        } else {
+            MemType::GB
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -46,48 +218,361 @@ pub struct Config {
     #[serde(default = "default_bool")]
     pub show_os: bool,
 
+    /// Shows both the title and the host unless they are set on their own.
     #[serde(default = "default_bool")]
     pub show_hostname: bool,
 
+    /// Falls back to `show_hostname` when unset.
+    #[serde(default)]
+    pub show_title: Option<bool>,
+
+    /// Falls back to `show_hostname` when unset.
+    #[serde(default)]
+    pub show_host: Option<bool>,
+
+    #[serde(default = "default_bool")]
+    pub show_model: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_virt: bool,
+
     #[serde(default = "default_bool")]
     pub show_uptime: bool,
 
+    #[serde(default = "bool_false_override")]
+    pub show_boot_time: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_datetime: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_timezone: bool,
+
+    /// strftime-style format of the current and the boot time, see [crate::datetime::DateTime::format].
+    #[serde(default = "default_datetime_format")]
+    pub datetime_format: String,
+
+    #[serde(default = "bool_false_override")]
+    pub show_load_avg: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_processes: bool,
+
     #[serde(default = "default_bool")]
     pub show_kernel_version: bool,
 
+    #[serde(default = "bool_false_override")]
+    pub show_arch: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_init: bool,
+
     #[serde(default = "default_bool")]
     pub show_memory: bool,
 
+    #[serde(default = "bool_false_override")]
+    pub show_memory_percent: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub memory_detailed: bool,
+
+    /// Shows the bars of memory, swap and disks unless they are set on their own.
+    #[serde(default = "bool_false_override")]
+    pub usage_bars: bool,
+
+    /// Falls back to `usage_bars` when unset.
+    #[serde(default)]
+    pub memory_bar: Option<bool>,
+
+    /// Falls back to `usage_bars` when unset.
+    #[serde(default)]
+    pub swap_bar: Option<bool>,
+
+    #[serde(default = "default_bar_width")]
+    pub bar_width: usize,
+
+    #[serde(default = "default_disk_warn_percent")]
+    pub disk_warn_percent: u8,
+
+    #[serde(default = "default_disk_crit_percent")]
+    pub disk_crit_percent: u8,
+
     #[serde(default = "default_bool")]
     pub show_de: bool,
 
+    #[serde(default = "default_bool")]
+    pub show_wm: bool,
+
+    #[serde(default = "default_bool")]
+    pub show_display_server: bool,
+
+    #[serde(default = "default_bool")]
+    pub show_packages: bool,
+
+    #[serde(default = "default_bool")]
+    pub show_shell: bool,
+
+    #[serde(default = "default_bool")]
+    pub show_terminal: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_locale: bool,
+
     #[serde(default = "default_bool")]
     pub show_swap: bool,
 
+    #[serde(default = "bool_false_override")]
+    pub force_swap: bool,
+
+    /// Shows only the used percentage of the swap, e.g. `Swap: 3%`.
+    #[serde(default = "bool_false_override")]
+    pub swap_percent_only: bool,
+
     #[serde(default = "default_bool")]
     pub show_colors: bool,
 
+    #[serde(default = "bool_false_override")]
+    pub show_logo: bool,
+
+    /// Applied by [Config::show_everything].
+    #[serde(default = "bool_false_override")]
+    pub show_all: bool,
+
+    /// Module names in print order, replacing the `show_*` flags when set.
+    #[serde(default)]
+    pub modules: Option<Vec<String>>,
+
     #[serde(default = "default_bool")]
     pub show_cpu: bool,
 
     #[serde(default = "default_bool")]
     pub show_cores: bool,
 
+    #[serde(default = "bool_false_override")]
+    pub show_threads: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_cpu_usage: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_cpu_freq: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_cpu_codename: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_per_core_usage: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_cpu_temp: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_cpu_cache: bool,
+
+    #[serde(default = "default_bool")]
+    pub show_gpu: bool,
+
+    #[serde(default = "default_bool")]
+    pub show_resolution: bool,
+
     #[serde(default = "default_bool")]
     pub show_disks: bool,
 
+    #[serde(default = "bool_false_override")]
+    pub show_disk_mount: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_disk_fs: bool,
+
+    /// Mount points of the only disks to show, in print order, replacing the other disk filters.
+    #[serde(default)]
+    pub disks: Option<Vec<String>>,
+
+    /// Mount point prefixes of the only disks to show, all disks when empty.
+    #[serde(default)]
+    pub disk_include: Vec<String>,
+
+    /// Mount point prefixes of disks to hide.
+    #[serde(default = "default_disk_exclude")]
+    pub disk_exclude: Vec<String>,
+
+    #[serde(default = "bool_false_override")]
+    pub hide_removable: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_disk_total: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_local_ip: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub show_network: bool,
+
+    #[serde(default = "default_bool")]
+    pub show_battery: bool,
+
     #[serde(default = "bool_false_override")]
     pub show_temperature: bool,
 
+    #[serde(default = "default_separator_char")]
+    pub separator_char: char,
+
+    #[serde(default = "default_separator_length")]
+    pub separator_length: usize,
+
+    /// Makes the separator as long as the `user@host` title, ignoring `separator_length`.
+    #[serde(default = "bool_false_override")]
+    pub separator_match_title: bool,
+
+    /// Labels overriding the built-in ones, by module name.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+
+    #[serde(default = "bool_false_override")]
+    pub show_icons: bool,
+
+    /// Icons overriding the built-in ones, by module name.
+    #[serde(default)]
+    pub icons: HashMap<String, String>,
+
+    #[serde(default = "default_bool")]
+    pub align_labels: bool,
+
+    /// Columns a line may take up before it's cut, the terminal's width when unset.
+    #[serde(default)]
+    pub max_width: Option<usize>,
+
+    #[serde(default = "default_bool")]
+    pub colored_output: bool,
+
+    /// `default`, `mono` or `minimal`, a preset of options the config itself may override.
+    #[serde(default)]
+    pub theme: Option<String>,
+
+    /// Color of the labels, checked by [Config::validate].
+    #[serde(default = "default_label_color")]
+    pub label_color: String,
+
+    /// Color of the values, left uncolored when unset.
+    #[serde(default)]
+    pub value_color: Option<String>,
+
     #[serde(default = "default_usize")]
     pub colors_height: usize,
 
     #[serde(default = "default_usize")]
     pub colors_width: usize,
 
+    #[serde(default = "default_bool")]
+    pub show_bright_colors: bool,
+
+    #[serde(default = "bool_false_override")]
+    pub colors_compact: bool,
+
+    /// Colors of the blocks, replacing both default rows when set.
+    #[serde(default)]
+    pub color_palette: Option<Vec<String>>,
+
     #[serde(default = "Time::default")]
     pub uptime_type: Time,
 
     #[serde(default = "MemType::default")]
     pub memory_type: MemType,
+
+    /// Decimal places of the memory, swap, disk and network sizes and of the uptime.
+    #[serde(default = "default_precision")]
+    pub precision: usize,
+
+    #[serde(default = "TempUnit::default")]
+    pub temperature_unit: TempUnit,
+
+    /// In °C whatever the `temperature_unit`.
+    #[serde(default = "default_temp_warn")]
+    pub temp_warn: u8,
+
+    /// In °C whatever the `temperature_unit`.
+    #[serde(default = "default_temp_crit")]
+    pub temp_crit: u8,
+
+    /// Label substrings of the only sensors to show, all sensors when empty.
+    #[serde(default)]
+    pub temp_filter: Vec<String>,
+
+    /// Falls back to `memory_type` when unset.
+    #[serde(default)]
+    pub swap_type: Option<MemType>,
+
+    /// Falls back to `memory_type` when unset.
+    #[serde(default)]
+    pub disk_type: Option<MemType>,
+
+    /// ASCII art file shown instead of the built-in logo, may contain ANSI colors.
+    #[serde(default)]
+    pub logo_path: Option<PathBuf>,
+
+    /// Keys of the config file that aren't options, reported by [Config::validate].
+    #[serde(skip)]
+    pub unknown_keys: Vec<String>,
+
+    /// `RUFETCH_SHOW_*` variables with a value that isn't a bool, reported by [Config::validate].
+    #[serde(skip)]
+    pub invalid_env: Vec<String>,
+
+    /// Set by the watch mode rather than the config file.
+    #[serde(skip)]
+    pub traffic: Traffic,
+}
+
+/// Errors that can occur while loading the config file.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config file exists but couldn't be read.
+    Io(io::Error),
+    /// The config file isn't valid TOML or has invalid values.
+    Parse(toml::de::Error),
+    /// The JSON or YAML config file has a syntax error.
+    Syntax(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "Could not read config: {}", error),
+            ConfigError::Parse(error) => write!(f, "{}", error),
+            ConfigError::Syntax(error) => write!(f, "Invalid config: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(error: toml::de::Error) -> Self {
+        ConfigError::Parse(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_parse_by_name_or_hex() {
+        assert_eq!(parse_color("red"), Some(Color::Red));
+        assert_eq!(parse_color("Cyan"), Some(Color::Cyan));
+        assert_eq!(parse_color("magenta"), Some(Color::Purple));
+        assert_eq!(parse_color("#ff8800"), Some(Color::RGB(0xff, 0x88, 0x00)));
+        assert_eq!(parse_color("#FF8800"), Some(Color::RGB(0xff, 0x88, 0x00)));
+        assert_eq!(parse_color("#f80"), None);
+        assert_eq!(parse_color("#gg8800"), None);
+        assert_eq!(parse_color("#ff88éé"), None);
+        assert_eq!(parse_color("orange"), None);
+        assert_eq!(parse_color(""), None);
+    }
 }
@@ -0,0 +1,260 @@
+use crate::json::Value;
+
+/// Parses the subset of YAML a config file needs: nested block mappings, block and
+/// flow (`[a, b]`) sequences of scalars, plain and quoted scalars and `#` comments.
+///
+/// # Errors
+/// Returns a message with the line number of the first line that can't be parsed.
+pub fn parse(text: &str) -> Result<Value, String> {
+    let lines: Vec<(usize, usize, &str)> = text
+        .lines()
+        .enumerate()
+        .filter_map(|(number, line)| {
+            let line = strip_comment(line).trim_end();
+            let content = line.trim_start();
+            if content.is_empty() || content == "---" {
+                return None;
+            }
+            Some((number + 1, line.len() - content.len(), content))
+        })
+        .collect();
+
+    let mut next = 0;
+    let value = match lines.first() {
+        Some(&(_, indent, _)) => block(&lines, &mut next, indent)?,
+        None => Value::Object(Vec::new()),
+    };
+    match lines.get(next) {
+        None => Ok(value),
+        Some((number, _, _)) => Err(format!("unexpected indentation on line {}", number)),
+    }
+}
+
+/// Parses the mapping or sequence whose lines start at `indent`, advancing `next` past it.
+fn block(lines: &[(usize, usize, &str)], next: &mut usize, indent: usize) -> Result<Value, String> {
+    let is_sequence = lines[*next].2.starts_with('-');
+    let mut values = Vec::new();
+    let mut pairs = Vec::new();
+
+    while let Some(&(number, line_indent, content)) = lines.get(*next) {
+        // A sequence may sit at the indentation of the key it belongs to
+        if line_indent != indent || is_sequence && !content.starts_with('-') {
+            break;
+        }
+        *next += 1;
+
+        if is_sequence {
+            match content.strip_prefix('-') {
+                Some(item) if item.is_empty() || item.starts_with(' ') => {
+                    values.push(scalar(item.trim(), number)?)
+                }
+                _ => return Err(format!("expected a `- ` list item on line {}", number)),
+            }
+            continue;
+        }
+
+        let (key, rest) = split_key(content)
+            .ok_or_else(|| format!("expected `key: value` on line {}", number))?;
+        let value = match lines.get(*next) {
+            Some(&(_, child_indent, _)) if rest.is_empty() && child_indent > indent => {
+                block(lines, next, child_indent)?
+            }
+            Some(&(_, child_indent, child))
+                if rest.is_empty() && child_indent == indent && child.starts_with("- ") =>
+            {
+                block(lines, next, child_indent)?
+            }
+            _ if rest.is_empty() => Value::Null,
+            _ => scalar(rest, number)?,
+        };
+        pairs.push((key, value));
+    }
+
+    Ok(if is_sequence {
+        Value::Array(values)
+    } else {
+        Value::Object(pairs)
+    })
+}
+
+/// Splits `key: value` at the first `:` followed by a space or the end of the line.
+fn split_key(content: &str) -> Option<(String, &str)> {
+    let (key, rest) = if let Some(quote @ ('"' | '\'')) = content.chars().next() {
+        let end = content[1..].find(quote)? + 1;
+        let rest = content[end + 1..].strip_prefix(':')?;
+        (content[1..end].to_string(), rest)
+    } else {
+        let colon = content
+            .match_indices(':')
+            .map(|(i, _)| i)
+            .find(|&i| content[i + 1..].is_empty() || content[i + 1..].starts_with(' '))?;
+        (
+            content[..colon].trim_end().to_string(),
+            &content[colon + 1..],
+        )
+    };
+
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((key, rest.trim()))
+}
+
+/// Parses a scalar or a flow sequence of scalars.
+fn scalar(text: &str, number: usize) -> Result<Value, String> {
+    if let Some(items) = text.strip_prefix('[') {
+        let items = items
+            .strip_suffix(']')
+            .ok_or_else(|| format!("expected a closing `]` on line {}", number))?;
+        return split_flow(items)
+            .into_iter()
+            .filter(|item| !item.is_empty())
+            .map(|item| scalar(item, number))
+            .collect::<Result<Vec<Value>, String>>()
+            .map(Value::Array);
+    }
+
+    if let Some(quoted) = text.strip_prefix('\'') {
+        return match quoted.strip_suffix('\'') {
+            Some(quoted) => Ok(Value::String(quoted.replace("''", "'"))),
+            None => Err(format!("expected a closing `'` on line {}", number)),
+        };
+    }
+    if text.starts_with('"') {
+        return Value::parse(text).map_err(|_| format!("invalid quoted string on line {}", number));
+    }
+
+    Ok(match text {
+        "" | "~" | "null" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => match text.parse() {
+            Ok(number) => Value::Number(number),
+            Err(_) => Value::String(text.to_string()),
+        },
+    })
+}
+
+/// Splits the items of a flow sequence at commas outside of quotes.
+fn split_flow(items: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in items.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, ',') => {
+                parts.push(items[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(items[start..].trim());
+    parts
+}
+
+/// Removes a `#` comment that starts the line or follows a space, outside of quotes.
+/// Quotes only count at the start of a key or value, so `Tom's OS # mine` keeps `Tom's OS`.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    // The last character outside of whitespace, `None` at the start of the line
+    let mut last = None;
+
+    for (i, c) in line.char_indices() {
+        let starts_value = match last {
+            None | Some('[' | ',') => true,
+            Some(':' | '-') => previous.is_whitespace(),
+            _ => false,
+        };
+        match (quote, c) {
+            (None, '"' | '\'') if starts_value => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, '#') if previous.is_whitespace() => return &line[..i],
+            _ => {}
+        }
+        previous = c;
+        if !c.is_whitespace() {
+            last = Some(c);
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses `text` and writes the value back out as JSON.
+    fn json(text: &str) -> String {
+        parse(text).unwrap().to_string()
+    }
+
+    #[test]
+    fn nested_mappings_and_scalars() {
+        assert_eq!(
+            json("show_os: true\nbar_width: 12\nlabels:\n  os: System\n  cpu: Processor\n"),
+            r#"{"show_os":true,"bar_width":12,"labels":{"os":"System","cpu":"Processor"}}"#
+        );
+    }
+
+    #[test]
+    fn block_and_flow_lists() {
+        let expected = r#"{"modules":["os","cpu"]}"#;
+        assert_eq!(json("modules:\n  - os\n  - cpu\n"), expected);
+        assert_eq!(json("modules:\n- os\n- cpu\n"), expected);
+        assert_eq!(json("modules: [os, \"cpu\"]\n"), expected);
+        assert_eq!(json("modules: []\n"), r#"{"modules":[]}"#);
+    }
+
+    #[test]
+    fn comments_outside_of_quotes() {
+        assert_eq!(
+            json("# a comment\n---\nos: Tom's OS # my distro\nsep: \"#\" # hash\nquote: 'it''s' # c\nurl: a#b\n"),
+            r##"{"os":"Tom's OS","sep":"#","quote":"it's","url":"a#b"}"##
+        );
+        assert_eq!(
+            json("modules: ['a # b', it's] # list\n"),
+            r#"{"modules":["a # b","it's"]}"#
+        );
+    }
+
+    #[test]
+    fn quoted_scalars_and_nulls() {
+        assert_eq!(
+            json("icon: \"\\uf17c\"\nname: 'true'\n'quoted key': 1\nempty:\ntilde: ~\n"),
+            "{\"icon\":\"\u{f17c}\",\"name\":\"true\",\"quoted key\":1,\"empty\":null,\"tilde\":null}"
+        );
+    }
+
+    #[test]
+    fn empty_documents_are_empty_mappings() {
+        assert_eq!(json("# nothing\n\n"), "{}");
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |text| parse(text).unwrap_err();
+        assert_eq!(
+            error("show_os: true\nnot a pair\n"),
+            "expected `key: value` on line 2"
+        );
+        assert_eq!(
+            error("modules: [os, cpu\n"),
+            "expected a closing `]` on line 1"
+        );
+        assert_eq!(
+            error("a: 1\n    b: 2\n"),
+            "unexpected indentation on line 2"
+        );
+        assert_eq!(error("name: 'open\n"), "expected a closing `'` on line 1");
+        assert_eq!(
+            error("modules:\n  - os\n  -cpu\n"),
+            "expected a `- ` list item on line 3"
+        );
+        assert_eq!(error("icon: \"\\q\"\n"), "invalid quoted string on line 1");
+    }
+}